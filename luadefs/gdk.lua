@@ -10,7 +10,7 @@ local gdk = {}
 ---@field b number?
 ---@field a number?
 
----@class Texture
+---@class Texture : ObjectImpl
 gdk.Texture = {
     ---@param data string
     ---@return Texture
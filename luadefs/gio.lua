@@ -26,7 +26,7 @@ local InputStreamAsyncBufRead = {
     read_to_end = function(self, size) end,
 }
 
----@class InputStream
+---@class InputStream : ObjectImpl
 local InputStream = {
     ---@param self InputStream
     ---@param buffer_size integer
@@ -49,7 +49,7 @@ local InputStream = {
     close = function(self) end
 }
 
----@class OutputStream
+---@class OutputStream : ObjectImpl
 local OutputStream = {
     ---@async
     ---@param self OutputStream
@@ -88,7 +88,7 @@ local OutputStream = {
 ---@field stderr_merge boolean?
 ---@field inherit_fds boolean?
 
----@class Subprocess
+---@class Subprocess : ObjectImpl
 gio.Subprocess = {
     ---@param args string[]
     ---@param flags? SubprocessFlags
@@ -146,7 +146,7 @@ gio.Subprocess = {
 }
 
 
----@class SocketConnection
+---@class SocketConnection : ObjectImpl
 local SocketConnection = {
     ---@param self SocketConnection
     ---@return InputStream
@@ -161,7 +161,7 @@ local SocketConnection = {
     close = function(self) end
 }
 
----@class SocketClient
+---@class SocketClient : ObjectImpl
 gio.SocketClient = {
     ---@return SocketClient
     new = function() end,
@@ -173,7 +173,7 @@ gio.SocketClient = {
     connect_unix = function(self, path) end
 }
 
----@class File
+---@class File : ObjectImpl
 gio.File = {
     ---@param path string
     ---@return File
@@ -199,21 +199,21 @@ gio.File = {
     replace = function(self) end,
 }
 
----@class ThemedIcon
+---@class ThemedIcon : ObjectImpl
 gio.ThemedIcon = {
     ---@param self ThemedIcon
     ---@return string[]
     names = function(self) end
 }
 
----@class Icon
+---@class Icon : ObjectImpl
 gio.Icon = {
     ---@param self Icon
     ---@return ThemedIcon
     as_themed = function(self) end
 }
 
----@class AppInfoMonitor
+---@class AppInfoMonitor : ObjectImpl
 gio.AppInfoMonitor = {
     ---@return AppInfoMonitor
     get = function() end,
@@ -226,7 +226,7 @@ gio.AppInfoMonitor = {
 ---@class Icon
 local Icon = {}
 
----@class AppInfo
+---@class AppInfo : ObjectImpl
 gio.AppInfo = {
     ---@return AppInfo[]
     all = function() end,
@@ -279,4 +279,173 @@ gio.AppInfo = {
     can_delete = function(self) end,
 }
 
+-- Menu model used by `PopoverMenu` and `MenuButton`.
+-- Items activate actions by their detailed name, e.g. `app.suspend` or `win.set-volume(50)`.
+-- Positions start from 0
+---@class Menu : ObjectImpl
+gio.Menu = {
+    ---@return Menu
+    new = function() end,
+
+    ---@param self Menu
+    ---@param label? string
+    ---@param action? string Detailed action name
+    append = function(self, label, action) end,
+
+    ---@param self Menu
+    ---@param label? string
+    ---@param action? string
+    prepend = function(self, label, action) end,
+
+    ---@param self Menu
+    ---@param position integer
+    ---@param label? string
+    ---@param action? string
+    insert = function(self, position, label, action) end,
+
+    -- Sections are separated from other items
+    ---@param self Menu
+    ---@param label? string
+    ---@param section Menu
+    append_section = function(self, label, section) end,
+
+    ---@param self Menu
+    ---@param label? string
+    ---@param submenu Menu
+    append_submenu = function(self, label, submenu) end,
+
+    -- Placeholder for a widget added with `PopoverMenu:add_child`
+    ---@param self Menu
+    ---@param id string
+    append_custom = function(self, id) end,
+
+    ---@param self Menu
+    ---@param position integer
+    remove = function(self, position) end,
+
+    ---@param self Menu
+    remove_all = function(self) end,
+
+    ---@param self Menu
+    ---@return integer
+    n_items = function(self) end,
+}
+
+-- Values of variants: booleans, integers, numbers and strings
+---@alias VariantValue boolean | integer | number | string
+
+-- Action that can be activated by menu items.
+-- Has to be added to an `Application` (`app.` prefix), `ApplicationWindow` (`win.` prefix)
+-- or a `SimpleActionGroup` inserted into a widget
+---@class SimpleAction : ObjectImpl
+gio.SimpleAction = {
+    ---@param name string
+    ---@param parameter_type? string Variant type of the parameter: "b", "y", "n", "q", "i", "u", "x", "t", "d" or "s"
+    ---@return SimpleAction
+    new = function(name, parameter_type) end,
+
+    -- Stateful boolean actions are shown as check items and toggled on activation.
+    -- Actions with a string state and parameter are shown as radio items
+    ---@param name string
+    ---@param state VariantValue
+    ---@param parameter_type? string
+    ---@param state_type? string Defaults to `parameter_type`, or is guessed from `state` if both are nil
+    ---@return SimpleAction
+    new_stateful = function(name, state, parameter_type, state_type) end,
+
+    ---@param self SimpleAction
+    ---@return string
+    name = function(self) end,
+
+    ---@param self SimpleAction
+    ---@param enabled boolean
+    set_enabled = function(self, enabled) end,
+
+    ---@param self SimpleAction
+    ---@return boolean
+    is_enabled = function(self) end,
+
+    -- Throws an error if `state` doesn't match the action's state type
+    ---@param self SimpleAction
+    ---@param state VariantValue
+    set_state = function(self, state) end,
+
+    ---@param self SimpleAction
+    ---@return VariantValue?
+    state = function(self) end,
+
+    -- Throws an error if `parameter` doesn't match the action's parameter type
+    ---@param self SimpleAction
+    ---@param parameter? VariantValue
+    activate = function(self, parameter) end,
+
+    ---@param self SimpleAction
+    ---@param callback fun(parameter: VariantValue?):nil
+    connect_activate = function(self, callback) end,
+
+    -- Called when a state change is requested.
+    -- Replaces the default handler, so the callback has to call `set_state` itself
+    ---@param self SimpleAction
+    ---@param callback fun(value: VariantValue?):nil
+    connect_change_state = function(self, callback) end,
+}
+
+---@class SimpleActionGroup : ObjectImpl
+gio.SimpleActionGroup = {
+    ---@return SimpleActionGroup
+    new = function() end,
+
+    ---@param self SimpleActionGroup
+    ---@param action SimpleAction
+    add_action = function(self, action) end,
+
+    ---@param self SimpleActionGroup
+    ---@param name string
+    remove_action = function(self, name) end,
+}
+
+---@class ListStore : ListModelImpl
+gio.ListStore = {
+    ---@param values? any[]
+    ---@return ListStore
+    new = function(values) end,
+
+    ---@param self ListStore
+    ---@return ListModel
+    upcast = function(self) end,
+
+    ---@param self ListStore
+    ---@param value any
+    append = function(self, value) end,
+
+    -- Throws an error if `position` is greater than `n_items`
+    ---@param self ListStore
+    ---@param position integer
+    ---@param value any
+    insert = function(self, position, value) end,
+
+    -- Replaces the value at `position`. Throws an error if it's out of bounds
+    ---@param self ListStore
+    ---@param position integer
+    ---@param value any
+    set = function(self, position, value) end,
+
+    -- Throws an error if `position` is out of bounds
+    ---@param self ListStore
+    ---@param position integer
+    remove = function(self, position) end,
+
+    ---@param self ListStore
+    remove_all = function(self) end,
+
+    -- Removes `n_removals` values starting at `position` and inserts `values` in their place,
+    -- notifying views only once. Faster than separate calls when replacing many values.
+    -- Throws an error if the removed range doesn't fit into the store
+    ---@param self ListStore
+    ---@param position integer
+    ---@param n_removals integer
+    ---@param values any[]
+    splice = function(self, position, n_removals, values) end,
+}
+
 crabshell.gio = gio
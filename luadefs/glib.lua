@@ -123,6 +123,81 @@ glib.Value       = {
 
 }
 
+-- Immutable byte buffer that can be sent to workers
+---@class Bytes
+glib.Bytes       = {
+    ---@param data string
+    ---@return Bytes
+    new = function(data) end,
+
+    ---@param self Bytes
+    ---@return integer
+    len = function(self) end,
+
+    ---@param self Bytes
+    ---@return string
+    to_str = function(self) end,
+}
+
+---@class BindingFlags
+---@field bidirectional boolean?
+---@field sync_create boolean?
+---@field invert_boolean boolean?
+
+-- Properties, signal arguments and return values are converted between `Value` and lua values,
+-- objects are passed as `Widget` if possible, or as `Object` otherwise
+---@class ObjectImpl
+local ObjectImpl = {
+    ---@param self ObjectImpl
+    ---@return Object
+    as_object = function(self) end,
+
+    -- Converts the value to the type of the property. Enums can be set as integers or nicks,
+    -- flags as integers or tables like `{ nick = true }`, objects as any object userdata
+    ---@param self ObjectImpl
+    ---@param property_name string
+    ---@param value any
+    set_property = function(self, property_name, value) end,
+
+    -- Enums are returned as nicks, flags as tables of nicks, with `-` replaced by `_`.
+    -- Values of types without a lua equivalent are returned as `Value`
+    ---@param self ObjectImpl
+    ---@param property_name string
+    ---@return any
+    property = function(self, property_name) end,
+
+    ---@param self ObjectImpl
+    ---@param signal_name string Detailed signals like `notify::label` are supported
+    ---@param callback fun(...):any
+    ---@return SignalHandlerId
+    connect = function(self, signal_name, callback) end,
+
+    ---@param self ObjectImpl
+    ---@param handler_id SignalHandlerId
+    disconnect = function(self, handler_id) end,
+
+    ---@param self ObjectImpl
+    ---@param source_property string
+    ---@param target ObjectImpl
+    ---@param target_property string
+    ---@param flags BindingFlags?
+    ---@return Binding
+    bind_property = function(self, source_property, target, target_property, flags) end,
+}
+
+-- Object that has no more specific class
+---@class Object : ObjectImpl
+local Object = {}
+
+---@class SignalHandlerId
+local SignalHandlerId = {}
+
+---@class Binding : ObjectImpl
+local Binding    = {
+    ---@param self Binding
+    unbind = function(self) end,
+}
+
 ---@class MainContext
 glib.MainContext = {
     ---@return MainContext
@@ -73,6 +73,66 @@ gtk.RevealerTransitionType = {
     SwingDown = 9
 }
 
+---@enum StackTransitionType
+gtk.StackTransitionType    = {
+    None = 0,
+    Crossfade = 1,
+    SlideRight = 2,
+    SlideLeft = 3,
+    SlideUp = 4,
+    SlideDown = 5,
+    SlideLeftRight = 6,
+    SlideUpDown = 7,
+    OverUp = 8,
+    OverDown = 9,
+    OverLeft = 10,
+    OverRight = 11,
+    UnderUp = 12,
+    UnderDown = 13,
+    UnderLeft = 14,
+    UnderRight = 15,
+    OverUpDown = 16,
+    OverDownUp = 17,
+    OverLeftRight = 18,
+    OverRightLeft = 19,
+    RotateLeft = 20,
+    RotateRight = 21,
+    RotateLeftRight = 22
+}
+
+---@enum PositionType
+gtk.PositionType           = {
+    Left = 0,
+    Right = 1,
+    Top = 2,
+    Bottom = 3
+}
+
+---@enum ArrowType
+gtk.ArrowType              = {
+    Up = 0,
+    Down = 1,
+    Left = 2,
+    Right = 3,
+    None = 4
+}
+
+---@enum PolicyType
+gtk.PolicyType             = {
+    Always = 0,
+    Automatic = 1,
+    Never = 2,
+    External = 3
+}
+
+---@enum SelectionMode
+gtk.SelectionMode          = {
+    None = 0,
+    Single = 1,
+    Browse = 2,
+    Multiple = 3
+}
+
 ---@class ApplicationFlags
 ---@field is_service boolean?
 ---@field is_launcher boolean?
@@ -84,7 +144,7 @@ gtk.RevealerTransitionType = {
 ---@field allow_replacement boolean?
 ---@field replace boolean?
 
----@class Application
+---@class Application : ObjectImpl
 gtk.Application            = {
     ---@param id string
     ---@param flags ApplicationFlags?
@@ -104,25 +164,24 @@ gtk.Application            = {
     connect_shutdown = function(self, callback) end,
 
     ---@param self Application
-    run = function(self) end
+    run = function(self) end,
+
+    -- Makes the action available as `app.name`
+    ---@param self Application
+    ---@param action SimpleAction
+    add_action = function(self, action) end,
+
+    ---@param self Application
+    ---@param name string
+    remove_action = function(self, name) end
 }
 
----@class WidgetImpl
+---@class WidgetImpl : ObjectImpl
 local WidgetImpl           = {
     ---@param self WidgetImpl
     ---@return Widget
     upcast = function(self) end,
 
-    ---@param self WidgetImpl
-    ---@param property_name string
-    ---@param value Value
-    set_property = function(self, property_name, value) end,
-
-    ---@param self WidgetImpl
-    ---@param property_name string
-    ---@return Value
-    property = function(self, property_name) end,
-
     ---@param self WidgetImpl
     ---@param controller EventController
     add_controller = function(self, controller) end,
@@ -238,6 +297,12 @@ local WidgetImpl           = {
     ---@param self WidgetImpl
     ---@return integer
     allocated_height = function(self) end,
+
+    -- Makes actions of `group` available to the widget and its children as `prefix.name`
+    ---@param self WidgetImpl
+    ---@param prefix string
+    ---@param group? SimpleActionGroup Removes the group if nil
+    insert_action_group = function(self, prefix, group) end,
 }
 
 ---@class Widget
@@ -261,7 +326,16 @@ gtk.ApplicationWindow      = {
     close = function(self) end,
 
     ---@param self ApplicationWindow
-    present = function(self) end
+    present = function(self) end,
+
+    -- Makes the action available to widgets of the window as `win.name`
+    ---@param self ApplicationWindow
+    ---@param action SimpleAction
+    add_action = function(self, action) end,
+
+    ---@param self ApplicationWindow
+    ---@param name string
+    remove_action = function(self, name) end
 }
 
 ---@class Box : WidgetImpl
@@ -466,7 +540,7 @@ gtk.Label                  = {
     set_ellipsize = function(self, mode) end
 }
 
----@class EntryBuffer
+---@class EntryBuffer : ObjectImpl
 local EntryBuffer          = {
     ---@param self EntryBuffer
     ---@return string
@@ -822,7 +896,1089 @@ gtk.Revealer               = {
     set_transition_type = function(self, transition) end,
 }
 
----@class EventControllerImpl
+---@class Stack : WidgetImpl
+gtk.Stack                  = {
+    ---@return Stack
+    new = function() end,
+
+    ---@param self Stack
+    ---@param callback fun():nil
+    connect_visible_child_notify = function(self, callback) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    add_child = function(self, child) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    ---@param name? string
+    add_named = function(self, child, name) end,
+
+    -- Adds a page with a title, which is shown by `StackSwitcher` and `StackSidebar`
+    ---@param self Stack
+    ---@param child Widget
+    ---@param name string?
+    ---@param title string
+    add_titled = function(self, child, name, title) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    remove = function(self, child) end,
+
+    ---@param self Stack
+    ---@param name string
+    ---@return Widget?
+    child_by_name = function(self, name) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    set_visible_child = function(self, child) end,
+
+    ---@param self Stack
+    ---@return Widget?
+    visible_child = function(self) end,
+
+    ---@param self Stack
+    ---@param name string
+    ---@param transition? StackTransitionType Overrides the transition type for this change
+    set_visible_child_name = function(self, name, transition) end,
+
+    ---@param self Stack
+    ---@return string?
+    visible_child_name = function(self) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    ---@param title string
+    set_page_title = function(self, child, title) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    ---@param icon_name string
+    set_page_icon_name = function(self, child, icon_name) end,
+
+    ---@param self Stack
+    ---@param child Widget
+    ---@param needs_attention boolean
+    set_page_needs_attention = function(self, child, needs_attention) end,
+
+    ---@param self Stack
+    ---@param transition StackTransitionType
+    set_transition_type = function(self, transition) end,
+
+    ---@param self Stack
+    ---@return StackTransitionType
+    transition_type = function(self) end,
+
+    ---@param self Stack
+    ---@param duration integer Duration in milliseconds
+    set_transition_duration = function(self, duration) end,
+
+    ---@param self Stack
+    ---@return integer
+    transition_duration = function(self) end,
+
+    ---@param self Stack
+    ---@return boolean
+    is_transition_running = function(self) end,
+
+    ---@param self Stack
+    ---@param homogeneous boolean
+    set_hhomogeneous = function(self, homogeneous) end,
+
+    ---@param self Stack
+    ---@param homogeneous boolean
+    set_vhomogeneous = function(self, homogeneous) end,
+
+    ---@param self Stack
+    ---@param interpolate_size boolean
+    set_interpolate_size = function(self, interpolate_size) end,
+}
+
+-- Row of buttons that switch pages of a `Stack`
+---@class StackSwitcher : WidgetImpl
+gtk.StackSwitcher          = {
+    ---@param stack? Stack
+    ---@return StackSwitcher
+    new = function(stack) end,
+
+    ---@param self StackSwitcher
+    ---@param stack? Stack
+    set_stack = function(self, stack) end,
+}
+
+-- Sidebar that switches pages of a `Stack`
+---@class StackSidebar : WidgetImpl
+gtk.StackSidebar           = {
+    ---@param stack? Stack
+    ---@return StackSidebar
+    new = function(stack) end,
+
+    ---@param self StackSidebar
+    ---@param stack Stack
+    set_stack = function(self, stack) end,
+}
+
+-- Page numbers start from 0
+---@class Notebook : WidgetImpl
+gtk.Notebook               = {
+    ---@return Notebook
+    new = function() end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@param tab_label? Widget
+    ---@return integer page_num
+    append_page = function(self, child, tab_label) end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@param tab_label? Widget
+    ---@return integer page_num
+    prepend_page = function(self, child, tab_label) end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@param tab_label? Widget
+    ---@param position? integer Appends the page if nil
+    ---@return integer page_num
+    insert_page = function(self, child, tab_label, position) end,
+
+    ---@param self Notebook
+    ---@param page_num? integer Removes the last page if nil
+    remove_page = function(self, page_num) end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@return integer?
+    page_num = function(self, child) end,
+
+    ---@param self Notebook
+    ---@param page_num? integer Returns the last page if nil
+    ---@return Widget?
+    nth_page = function(self, page_num) end,
+
+    ---@param self Notebook
+    ---@return integer
+    n_pages = function(self) end,
+
+    ---@param self Notebook
+    ---@param page_num? integer Switches to the last page if nil
+    set_current_page = function(self, page_num) end,
+
+    ---@param self Notebook
+    ---@return integer?
+    current_page = function(self) end,
+
+    ---@param self Notebook
+    next_page = function(self) end,
+
+    ---@param self Notebook
+    prev_page = function(self) end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@param tab_label? Widget
+    set_tab_label = function(self, child, tab_label) end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@param text string
+    set_tab_label_text = function(self, child, text) end,
+
+    ---@param self Notebook
+    ---@param child Widget
+    ---@param reorderable boolean
+    set_tab_reorderable = function(self, child, reorderable) end,
+
+    ---@param self Notebook
+    ---@param pos PositionType
+    set_tab_pos = function(self, pos) end,
+
+    ---@param self Notebook
+    ---@param show_tabs boolean
+    set_show_tabs = function(self, show_tabs) end,
+
+    ---@param self Notebook
+    ---@param show_border boolean
+    set_show_border = function(self, show_border) end,
+
+    ---@param self Notebook
+    ---@param scrollable boolean
+    set_scrollable = function(self, scrollable) end,
+
+    ---@param self Notebook
+    ---@param callback fun(page_num: integer):nil
+    connect_switch_page = function(self, callback) end,
+}
+
+---@class ScrolledWindow : WidgetImpl
+gtk.ScrolledWindow         = {
+    ---@param child? Widget
+    ---@return ScrolledWindow
+    new = function(child) end,
+
+    ---@param self ScrolledWindow
+    ---@param child? Widget
+    set_child = function(self, child) end,
+
+    ---@param self ScrolledWindow
+    ---@param hpolicy PolicyType
+    ---@param vpolicy PolicyType
+    set_policy = function(self, hpolicy, vpolicy) end,
+
+    ---@param self ScrolledWindow
+    ---@return PolicyType
+    hscrollbar_policy = function(self) end,
+
+    ---@param self ScrolledWindow
+    ---@return PolicyType
+    vscrollbar_policy = function(self) end,
+
+    -- Makes the window request the natural width of its child instead of scrolling it
+    ---@param self ScrolledWindow
+    ---@param propagate boolean
+    set_propagate_natural_width = function(self, propagate) end,
+
+    -- Makes the window request the natural height of its child instead of scrolling it
+    ---@param self ScrolledWindow
+    ---@param propagate boolean
+    set_propagate_natural_height = function(self, propagate) end,
+
+    ---@param self ScrolledWindow
+    ---@param kinetic boolean
+    set_kinetic_scrolling = function(self, kinetic) end,
+
+    -- Whether scrollbars are drawn over the content and hidden when unused
+    ---@param self ScrolledWindow
+    ---@param overlay boolean
+    set_overlay_scrolling = function(self, overlay) end,
+
+    ---@param self ScrolledWindow
+    ---@param has_frame boolean
+    set_has_frame = function(self, has_frame) end,
+
+    ---@param self ScrolledWindow
+    ---@param width integer
+    set_min_content_width = function(self, width) end,
+
+    ---@param self ScrolledWindow
+    ---@param height integer
+    set_min_content_height = function(self, height) end,
+
+    -- Limits the width requested when natural width is propagated
+    ---@param self ScrolledWindow
+    ---@param width integer
+    set_max_content_width = function(self, width) end,
+
+    -- Limits the height requested when natural height is propagated
+    ---@param self ScrolledWindow
+    ---@param height integer
+    set_max_content_height = function(self, height) end,
+
+    -- Returns the horizontal scroll offset in pixels
+    ---@param self ScrolledWindow
+    ---@return number
+    hscroll = function(self) end,
+
+    -- Returns the vertical scroll offset in pixels
+    ---@param self ScrolledWindow
+    ---@return number
+    vscroll = function(self) end,
+
+    ---@param self ScrolledWindow
+    ---@param value number
+    set_hscroll = function(self, value) end,
+
+    ---@param self ScrolledWindow
+    ---@param value number
+    set_vscroll = function(self, value) end,
+
+    -- Called when the content is scrolled to one of its edges
+    ---@param self ScrolledWindow
+    ---@param callback fun(pos: PositionType):nil
+    connect_edge_reached = function(self, callback) end,
+}
+
+-- Children of a `ListBox` that aren't rows are wrapped in one automatically
+---@class ListBoxRow : WidgetImpl
+gtk.ListBoxRow             = {
+    ---@param child? Widget
+    ---@return ListBoxRow
+    new = function(child) end,
+
+    ---@param self ListBoxRow
+    ---@param child? Widget
+    set_child = function(self, child) end,
+
+    ---@param self ListBoxRow
+    ---@return Widget?
+    child = function(self) end,
+
+    -- Returns the position of the row, or -1 if it's not in a list box
+    ---@param self ListBoxRow
+    ---@return integer
+    index = function(self) end,
+
+    ---@param self ListBoxRow
+    ---@return boolean
+    is_selected = function(self) end,
+
+    ---@param self ListBoxRow
+    ---@param activatable boolean
+    set_activatable = function(self, activatable) end,
+
+    ---@param self ListBoxRow
+    ---@param selectable boolean
+    set_selectable = function(self, selectable) end,
+
+    -- Makes the list box filter and sort the row again
+    ---@param self ListBoxRow
+    changed = function(self) end,
+
+    ---@param self ListBoxRow
+    ---@param callback fun():nil
+    connect_activate = function(self, callback) end,
+}
+
+-- Vertical list of rows. Row positions start from 0
+---@class ListBox : WidgetImpl
+gtk.ListBox                = {
+    ---@return ListBox
+    new = function() end,
+
+    ---@param self ListBox
+    ---@param child Widget
+    prepend = function(self, child) end,
+
+    ---@param self ListBox
+    ---@param child Widget
+    append = function(self, child) end,
+
+    ---@param self ListBox
+    ---@param child Widget
+    ---@param position integer Appends the child if -1
+    insert = function(self, child, position) end,
+
+    ---@param self ListBox
+    ---@param child Widget
+    remove = function(self, child) end,
+
+    -- Removes every row, keeping the placeholder
+    ---@param self ListBox
+    remove_all = function(self) end,
+
+    ---@param self ListBox
+    ---@param index integer
+    ---@return ListBoxRow?
+    row_at_index = function(self, index) end,
+
+    -- Shown when there are no visible rows
+    ---@param self ListBox
+    ---@param placeholder? Widget
+    set_placeholder = function(self, placeholder) end,
+
+    ---@param self ListBox
+    ---@param mode SelectionMode
+    set_selection_mode = function(self, mode) end,
+
+    ---@param self ListBox
+    ---@return SelectionMode
+    selection_mode = function(self) end,
+
+    ---@param self ListBox
+    ---@param row? ListBoxRow Unselects all rows if nil
+    select_row = function(self, row) end,
+
+    ---@param self ListBox
+    ---@param row ListBoxRow
+    unselect_row = function(self, row) end,
+
+    ---@param self ListBox
+    select_all = function(self) end,
+
+    ---@param self ListBox
+    unselect_all = function(self) end,
+
+    ---@param self ListBox
+    ---@return ListBoxRow?
+    selected_row = function(self) end,
+
+    ---@param self ListBox
+    ---@return ListBoxRow[]
+    selected_rows = function(self) end,
+
+    ---@param self ListBox
+    ---@param single boolean
+    set_activate_on_single_click = function(self, single) end,
+
+    ---@param self ListBox
+    ---@param show_separators boolean
+    set_show_separators = function(self, show_separators) end,
+
+    ---@param self ListBox
+    ---@param callback fun(row: ListBoxRow):nil
+    connect_row_activated = function(self, callback) end,
+
+    ---@param self ListBox
+    ---@param callback fun(row: ListBoxRow?):nil
+    connect_row_selected = function(self, callback) end,
+
+    ---@param self ListBox
+    ---@param callback fun():nil
+    connect_selected_rows_changed = function(self, callback) end,
+
+    -- Rows for which `filter` returns false are hidden.
+    -- Rows are shown if it throws an error
+    ---@param self ListBox
+    ---@param filter fun(row: ListBoxRow):boolean
+    set_filter_func = function(self, filter) end,
+
+    ---@param self ListBox
+    unset_filter_func = function(self) end,
+
+    -- Calls the filter function for every row again
+    ---@param self ListBox
+    invalidate_filter = function(self) end,
+
+    -- `sort` returns a negative number if `a` goes before `b`,
+    -- a positive number if it goes after it, or 0 if their order doesn't matter
+    ---@param self ListBox
+    ---@param sort fun(a: ListBoxRow, b: ListBoxRow):number
+    set_sort_func = function(self, sort) end,
+
+    ---@param self ListBox
+    unset_sort_func = function(self) end,
+
+    -- Sorts all rows again
+    ---@param self ListBox
+    invalidate_sort = function(self) end,
+}
+
+-- Children of a `FlowBox` that aren't `FlowBoxChild` are wrapped in one automatically
+---@class FlowBoxChild : WidgetImpl
+gtk.FlowBoxChild           = {
+    ---@param child? Widget
+    ---@return FlowBoxChild
+    new = function(child) end,
+
+    ---@param self FlowBoxChild
+    ---@param child? Widget
+    set_child = function(self, child) end,
+
+    ---@param self FlowBoxChild
+    ---@return Widget?
+    child = function(self) end,
+
+    -- Returns the position of the child, or -1 if it's not in a flow box
+    ---@param self FlowBoxChild
+    ---@return integer
+    index = function(self) end,
+
+    ---@param self FlowBoxChild
+    ---@return boolean
+    is_selected = function(self) end,
+
+    -- Makes the flow box filter and sort the child again
+    ---@param self FlowBoxChild
+    changed = function(self) end,
+
+    ---@param self FlowBoxChild
+    ---@param callback fun():nil
+    connect_activate = function(self, callback) end,
+}
+
+-- Puts children in lines, wrapping them when a line is full. Suitable for icon grids
+---@class FlowBox : WidgetImpl
+gtk.FlowBox                = {
+    ---@return FlowBox
+    new = function() end,
+
+    ---@param self FlowBox
+    ---@param child Widget
+    prepend = function(self, child) end,
+
+    ---@param self FlowBox
+    ---@param child Widget
+    append = function(self, child) end,
+
+    ---@param self FlowBox
+    ---@param child Widget
+    ---@param position integer Appends the child if -1
+    insert = function(self, child, position) end,
+
+    ---@param self FlowBox
+    ---@param child Widget
+    remove = function(self, child) end,
+
+    ---@param self FlowBox
+    remove_all = function(self) end,
+
+    ---@param self FlowBox
+    ---@param index integer
+    ---@return FlowBoxChild?
+    child_at_index = function(self, index) end,
+
+    -- Lines are rows if `orientation` is horizontal, and columns otherwise
+    ---@param self FlowBox
+    ---@param orientation Orientation
+    set_orientation = function(self, orientation) end,
+
+    -- Whether all children get the same size
+    ---@param self FlowBox
+    ---@param homogeneous boolean
+    set_homogeneous = function(self, homogeneous) end,
+
+    ---@param self FlowBox
+    ---@param n_children integer
+    set_min_children_per_line = function(self, n_children) end,
+
+    ---@param self FlowBox
+    ---@param n_children integer
+    set_max_children_per_line = function(self, n_children) end,
+
+    ---@param self FlowBox
+    ---@param spacing integer
+    set_row_spacing = function(self, spacing) end,
+
+    ---@param self FlowBox
+    ---@param spacing integer
+    set_column_spacing = function(self, spacing) end,
+
+    ---@param self FlowBox
+    ---@param mode SelectionMode
+    set_selection_mode = function(self, mode) end,
+
+    ---@param self FlowBox
+    ---@return SelectionMode
+    selection_mode = function(self) end,
+
+    ---@param self FlowBox
+    ---@param child FlowBoxChild
+    select_child = function(self, child) end,
+
+    ---@param self FlowBox
+    ---@param child FlowBoxChild
+    unselect_child = function(self, child) end,
+
+    ---@param self FlowBox
+    select_all = function(self) end,
+
+    ---@param self FlowBox
+    unselect_all = function(self) end,
+
+    ---@param self FlowBox
+    ---@return FlowBoxChild[]
+    selected_children = function(self) end,
+
+    ---@param self FlowBox
+    ---@param single boolean
+    set_activate_on_single_click = function(self, single) end,
+
+    ---@param self FlowBox
+    ---@param callback fun(child: FlowBoxChild):nil
+    connect_child_activated = function(self, callback) end,
+
+    ---@param self FlowBox
+    ---@param callback fun():nil
+    connect_selected_children_changed = function(self, callback) end,
+
+    -- Children for which `filter` returns false are hidden.
+    -- Children are shown if it throws an error
+    ---@param self FlowBox
+    ---@param filter fun(child: FlowBoxChild):boolean
+    set_filter_func = function(self, filter) end,
+
+    ---@param self FlowBox
+    unset_filter_func = function(self) end,
+
+    -- Calls the filter function for every child again
+    ---@param self FlowBox
+    invalidate_filter = function(self) end,
+
+    -- `sort` returns a negative number if `a` goes before `b`,
+    -- a positive number if it goes after it, or 0 if their order doesn't matter
+    ---@param self FlowBox
+    ---@param sort fun(a: FlowBoxChild, b: FlowBoxChild):number
+    set_sort_func = function(self, sort) end,
+
+    ---@param self FlowBox
+    unset_sort_func = function(self) end,
+
+    -- Sorts all children again
+    ---@param self FlowBox
+    invalidate_sort = function(self) end,
+}
+
+-- Models hold any lua values as items, positions start from 0.
+-- Items that don't come from a `ListStore` are seen as nil
+---@class ListModelImpl : ObjectImpl
+local ListModelImpl        = {
+    ---@param self ListModelImpl
+    ---@return integer
+    n_items = function(self) end,
+
+    ---@param self ListModelImpl
+    ---@param position integer
+    ---@return any
+    get = function(self, position) end,
+
+    ---@param self ListModelImpl
+    ---@param callback fun(position: integer, removed: integer, added: integer):nil
+    connect_items_changed = function(self, callback) end,
+}
+
+---@class ListModel = {}
+
+---@class SelectionModel = {}
+
+-- Shows only items of another model for which the filter function returns true
+---@class FilterListModel : ListModelImpl
+gtk.FilterListModel        = {
+    -- Items are shown if `filter` throws an error
+    ---@param model ListModel
+    ---@param filter? fun(item: any):boolean
+    ---@return FilterListModel
+    new = function(model, filter) end,
+
+    ---@param self FilterListModel
+    ---@return ListModel
+    upcast = function(self) end,
+
+    ---@param self FilterListModel
+    ---@param filter fun(item: any):boolean
+    set_filter_func = function(self, filter) end,
+
+    ---@param self FilterListModel
+    unset_filter_func = function(self) end,
+
+    -- Calls the filter function for every item again, e.g. when the search query changes
+    ---@param self FilterListModel
+    invalidate_filter = function(self) end,
+
+    -- Whether to filter items in chunks, keeping the ui responsive for large models
+    ---@param self FilterListModel
+    ---@param incremental boolean
+    set_incremental = function(self, incremental) end,
+}
+
+-- Shows items of another model, sorted with the sort function
+---@class SortListModel : ListModelImpl
+gtk.SortListModel          = {
+    -- `sort` returns a negative number if `a` goes before `b`,
+    -- a positive number if it goes after it, or 0 if their order doesn't matter
+    ---@param model ListModel
+    ---@param sort? fun(a: any, b: any):number
+    ---@return SortListModel
+    new = function(model, sort) end,
+
+    ---@param self SortListModel
+    ---@return ListModel
+    upcast = function(self) end,
+
+    ---@param self SortListModel
+    ---@param sort fun(a: any, b: any):number
+    set_sort_func = function(self, sort) end,
+
+    ---@param self SortListModel
+    unset_sort_func = function(self) end,
+
+    -- Sorts all items again
+    ---@param self SortListModel
+    invalidate_sort = function(self) end,
+
+    -- Whether to sort items in chunks, keeping the ui responsive for large models
+    ---@param self SortListModel
+    ---@param incremental boolean
+    set_incremental = function(self, incremental) end,
+}
+
+-- Selection model that doesn't allow selecting items
+---@class NoSelection : ListModelImpl
+gtk.NoSelection            = {
+    ---@param model ListModel
+    ---@return NoSelection
+    new = function(model) end,
+
+    ---@param self NoSelection
+    ---@return SelectionModel
+    upcast = function(self) end,
+}
+
+---@class SingleSelection : ListModelImpl
+gtk.SingleSelection        = {
+    ---@param model ListModel
+    ---@return SingleSelection
+    new = function(model) end,
+
+    ---@param self SingleSelection
+    ---@return SelectionModel
+    upcast = function(self) end,
+
+    ---@param self SingleSelection
+    ---@return integer? position Nil if nothing is selected
+    selected = function(self) end,
+
+    ---@param self SingleSelection
+    ---@param position? integer Unselects the item if nil
+    set_selected = function(self, position) end,
+
+    ---@param self SingleSelection
+    ---@return any
+    selected_item = function(self) end,
+
+    -- Whether an item is always selected. True by default
+    ---@param self SingleSelection
+    ---@param autoselect boolean
+    set_autoselect = function(self, autoselect) end,
+
+    ---@param self SingleSelection
+    ---@param can_unselect boolean
+    set_can_unselect = function(self, can_unselect) end,
+
+    ---@param self SingleSelection
+    ---@param callback fun():nil
+    connect_selected_notify = function(self, callback) end,
+}
+
+---@class MultiSelection : ListModelImpl
+gtk.MultiSelection         = {
+    ---@param model ListModel
+    ---@return MultiSelection
+    new = function(model) end,
+
+    ---@param self MultiSelection
+    ---@return SelectionModel
+    upcast = function(self) end,
+
+    ---@param self MultiSelection
+    ---@param position integer
+    ---@return boolean
+    is_selected = function(self, position) end,
+
+    -- Returns false if the selection hasn't changed
+    ---@param self MultiSelection
+    ---@param position integer
+    ---@param unselect_rest? boolean
+    ---@return boolean
+    select_item = function(self, position, unselect_rest) end,
+
+    ---@param self MultiSelection
+    ---@param position integer
+    ---@return boolean
+    unselect_item = function(self, position) end,
+
+    ---@param self MultiSelection
+    ---@return boolean
+    select_all = function(self) end,
+
+    ---@param self MultiSelection
+    ---@return boolean
+    unselect_all = function(self) end,
+
+    -- Called with the range of items whose selection may have changed
+    ---@param self MultiSelection
+    ---@param callback fun(position: integer, n_items: integer):nil
+    connect_selection_changed = function(self, callback) end,
+}
+
+-- Row or cell of a `ListView` or `GridView`. List items are reused for different model items
+---@class ListItem : ObjectImpl
+local ListItem             = {
+    ---@param self ListItem
+    ---@param child? Widget
+    set_child = function(self, child) end,
+
+    ---@param self ListItem
+    ---@return Widget?
+    child = function(self) end,
+
+    ---@param self ListItem
+    ---@return any
+    item = function(self) end,
+
+    ---@param self ListItem
+    ---@return integer? position Nil if the list item isn't bound
+    position = function(self) end,
+
+    ---@param self ListItem
+    ---@return boolean
+    is_selected = function(self) end,
+
+    ---@param self ListItem
+    ---@param activatable boolean
+    set_activatable = function(self, activatable) end,
+
+    ---@param self ListItem
+    ---@param selectable boolean
+    set_selectable = function(self, selectable) end,
+}
+
+-- Creates widgets of list items with lua callbacks.
+-- `setup` creates the child widgets of a list item, and `bind` fills them with an item.
+-- Only as many list items as are visible get created, so views stay fast with large models
+---@class SignalListItemFactory : ObjectImpl
+gtk.SignalListItemFactory  = {
+    ---@param setup? fun(list_item: ListItem):nil
+    ---@param bind? fun(list_item: ListItem, item: any):nil
+    ---@return SignalListItemFactory
+    new = function(setup, bind) end,
+
+    ---@param self SignalListItemFactory
+    ---@param callback fun(list_item: ListItem):nil
+    connect_setup = function(self, callback) end,
+
+    ---@param self SignalListItemFactory
+    ---@param callback fun(list_item: ListItem, item: any):nil
+    connect_bind = function(self, callback) end,
+
+    -- Called before the list item gets bound to another item
+    ---@param self SignalListItemFactory
+    ---@param callback fun(list_item: ListItem, item: any):nil
+    connect_unbind = function(self, callback) end,
+
+    ---@param self SignalListItemFactory
+    ---@param callback fun(list_item: ListItem):nil
+    connect_teardown = function(self, callback) end,
+}
+
+-- Virtualized list. Should be put in a `ScrolledWindow`
+---@class ListView : WidgetImpl
+gtk.ListView               = {
+    ---@param model? SelectionModel
+    ---@param factory? SignalListItemFactory
+    ---@return ListView
+    new = function(model, factory) end,
+
+    ---@param self ListView
+    ---@param model? SelectionModel
+    set_model = function(self, model) end,
+
+    ---@param self ListView
+    ---@param factory? SignalListItemFactory
+    set_factory = function(self, factory) end,
+
+    ---@param self ListView
+    ---@param orientation Orientation
+    set_orientation = function(self, orientation) end,
+
+    ---@param self ListView
+    ---@param show_separators boolean
+    set_show_separators = function(self, show_separators) end,
+
+    ---@param self ListView
+    ---@param single boolean
+    set_single_click_activate = function(self, single) end,
+
+    ---@param self ListView
+    ---@param enable boolean
+    set_enable_rubberband = function(self, enable) end,
+
+    ---@param self ListView
+    ---@param callback fun(position: integer):nil
+    connect_activate = function(self, callback) end,
+}
+
+-- Virtualized grid. Should be put in a `ScrolledWindow`
+---@class GridView : WidgetImpl
+gtk.GridView               = {
+    ---@param model? SelectionModel
+    ---@param factory? SignalListItemFactory
+    ---@return GridView
+    new = function(model, factory) end,
+
+    ---@param self GridView
+    ---@param model? SelectionModel
+    set_model = function(self, model) end,
+
+    ---@param self GridView
+    ---@param factory? SignalListItemFactory
+    set_factory = function(self, factory) end,
+
+    ---@param self GridView
+    ---@param orientation Orientation
+    set_orientation = function(self, orientation) end,
+
+    ---@param self GridView
+    ---@param min_columns integer
+    set_min_columns = function(self, min_columns) end,
+
+    ---@param self GridView
+    ---@param max_columns integer
+    set_max_columns = function(self, max_columns) end,
+
+    ---@param self GridView
+    ---@param single boolean
+    set_single_click_activate = function(self, single) end,
+
+    ---@param self GridView
+    ---@param enable boolean
+    set_enable_rubberband = function(self, enable) end,
+
+    ---@param self GridView
+    ---@param callback fun(position: integer):nil
+    connect_activate = function(self, callback) end,
+}
+
+---@class PopoverImpl : WidgetImpl
+local PopoverImpl          = {
+    -- Attaches the popover to a widget, which it points to.
+    -- Call `unparent` before the widget is destroyed
+    ---@param self PopoverImpl
+    ---@param parent Widget
+    set_parent = function(self, parent) end,
+
+    ---@param self PopoverImpl
+    unparent = function(self) end,
+
+    -- Shows the popover. Layer shell windows that don't accept keyboard focus
+    -- take it on demand while any of their popovers is open, so that autohide works
+    ---@param self PopoverImpl
+    popup = function(self) end,
+
+    ---@param self PopoverImpl
+    popdown = function(self) end,
+
+    ---@param self PopoverImpl
+    ---@param child? Widget
+    set_child = function(self, child) end,
+
+    -- Preferred side of the parent, the popover is flipped if it doesn't fit
+    ---@param self PopoverImpl
+    ---@param position PositionType
+    set_position = function(self, position) end,
+
+    -- Whether to close the popover when clicking outside of it. True by default
+    ---@param self PopoverImpl
+    ---@param autohide boolean
+    set_autohide = function(self, autohide) end,
+
+    ---@param self PopoverImpl
+    ---@param has_arrow boolean
+    set_has_arrow = function(self, has_arrow) end,
+
+    ---@param self PopoverImpl
+    ---@param x integer
+    ---@param y integer
+    set_offset = function(self, x, y) end,
+
+    -- Points to a rectangle in the parent's coordinates instead of the whole parent.
+    -- Points to the whole parent if `x` or `y` is nil
+    ---@param self PopoverImpl
+    ---@param x? integer
+    ---@param y? integer
+    ---@param width? integer 1 by default
+    ---@param height? integer 1 by default
+    set_pointing_to = function(self, x, y, width, height) end,
+
+    -- Whether to close the popover when a child popover closes
+    ---@param self PopoverImpl
+    ---@param cascade_popdown boolean
+    set_cascade_popdown = function(self, cascade_popdown) end,
+
+    ---@param self PopoverImpl
+    ---@param callback fun():nil
+    connect_closed = function(self, callback) end,
+}
+
+---@class Popover : PopoverImpl
+gtk.Popover                = {
+    ---@param child? Widget
+    ---@return Popover
+    new = function(child) end,
+}
+
+---@class PopoverMenu : PopoverImpl
+gtk.PopoverMenu            = {
+    ---@param menu? Menu
+    ---@return PopoverMenu
+    new = function(menu) end,
+
+    ---@param self PopoverMenu
+    ---@param menu? Menu
+    set_menu_model = function(self, menu) end,
+
+    -- Puts a widget in place of the item added with `Menu:append_custom(id)`.
+    -- Returns false if there is no such item
+    ---@param self PopoverMenu
+    ---@param child Widget
+    ---@param id string
+    ---@return boolean
+    add_child = function(self, child, id) end,
+
+    ---@param self PopoverMenu
+    ---@param child Widget
+    ---@return boolean
+    remove_child = function(self, child) end,
+}
+
+-- Button that opens a popover or a menu when clicked
+---@class MenuButton : WidgetImpl
+gtk.MenuButton             = {
+    ---@param menu? Menu
+    ---@return MenuButton
+    new = function(menu) end,
+
+    -- Popovers of menu buttons get the same focus handling as `PopoverImpl.popup`
+    ---@param self MenuButton
+    ---@param popover? Widget
+    set_popover = function(self, popover) end,
+
+    ---@param self MenuButton
+    ---@return Popover?
+    popover = function(self) end,
+
+    ---@param self MenuButton
+    ---@param menu? Menu
+    set_menu_model = function(self, menu) end,
+
+    ---@param self MenuButton
+    ---@param label string
+    set_label = function(self, label) end,
+
+    ---@param self MenuButton
+    ---@param icon_name string
+    set_icon_name = function(self, icon_name) end,
+
+    ---@param self MenuButton
+    ---@param child? Widget
+    set_child = function(self, child) end,
+
+    -- Side of the button where the popover is shown
+    ---@param self MenuButton
+    ---@param direction ArrowType
+    set_direction = function(self, direction) end,
+
+    ---@param self MenuButton
+    ---@param has_frame boolean
+    set_has_frame = function(self, has_frame) end,
+
+    ---@param self MenuButton
+    ---@param always_show_arrow boolean
+    set_always_show_arrow = function(self, always_show_arrow) end,
+
+    -- Whether F10 opens the menu
+    ---@param self MenuButton
+    ---@param primary boolean
+    set_primary = function(self, primary) end,
+
+    -- Opens or closes the popover
+    ---@param self MenuButton
+    ---@param active boolean
+    set_active = function(self, active) end,
+
+    ---@param self MenuButton
+    ---@return boolean
+    is_active = function(self) end,
+
+    ---@param self MenuButton
+    popup = function(self) end,
+
+    ---@param self MenuButton
+    popdown = function(self) end,
+}
+
+---@class EventControllerImpl : ObjectImpl
 local EventControllerImpl  = {
     ---@param self EventControllerImpl
     ---@return EventController
@@ -924,7 +2080,125 @@ gtk.EventControllerFocus   = {
     connect_leave = function(self, callback) end,
 }
 
----@class IconPaintable
+-- Buttons are numbered from 1: 1 is primary, 2 is middle and 3 is secondary.
+-- Gestures react only to the primary button by default, 0 makes them react to any button
+---@class GestureImpl : EventControllerImpl
+local GestureImpl          = {
+    ---@param self GestureImpl
+    ---@param button integer
+    set_button = function(self, button) end,
+
+    ---@param self GestureImpl
+    ---@return integer
+    button = function(self) end,
+
+    -- Returns the button of the ongoing gesture, 0 if there's none
+    ---@param self GestureImpl
+    ---@return integer
+    current_button = function(self) end,
+
+    ---@param self GestureImpl
+    ---@param touch_only boolean
+    set_touch_only = function(self, touch_only) end,
+
+    -- Whether to ignore emulated pointer events
+    ---@param self GestureImpl
+    ---@param exclusive boolean
+    set_exclusive = function(self, exclusive) end,
+
+    -- Stops other gestures, e.g. of parent widgets, from handling the ongoing event sequence.
+    -- Returns whether the state has changed
+    ---@param self GestureImpl
+    ---@return boolean
+    claim = function(self) end,
+}
+
+---@class GestureClick : GestureImpl
+gtk.GestureClick           = {
+    ---@param button? integer
+    ---@return GestureClick
+    new = function(button) end,
+
+    -- `n_press` is 2 for double clicks, 3 for triple clicks and so on
+    ---@param self GestureClick
+    ---@param callback fun(n_press: integer, x: number, y: number, button: integer):nil
+    connect_pressed = function(self, callback) end,
+
+    ---@param self GestureClick
+    ---@param callback fun(n_press: integer, x: number, y: number, button: integer):nil
+    connect_released = function(self, callback) end,
+
+    -- Called when a click sequence ends without a release, e.g. when the pointer leaves the widget
+    ---@param self GestureClick
+    ---@param callback fun():nil
+    connect_stopped = function(self, callback) end,
+}
+
+---@class GestureLongPress : GestureImpl
+gtk.GestureLongPress       = {
+    ---@param button? integer
+    ---@return GestureLongPress
+    new = function(button) end,
+
+    ---@param self GestureLongPress
+    ---@param callback fun(x: number, y: number, button: integer):nil
+    connect_pressed = function(self, callback) end,
+
+    -- Called when the press is released or moved too far before the delay
+    ---@param self GestureLongPress
+    ---@param callback fun():nil
+    connect_cancelled = function(self, callback) end,
+
+    -- Multiplies the default delay
+    ---@param self GestureLongPress
+    ---@param delay_factor number
+    set_delay_factor = function(self, delay_factor) end,
+}
+
+---@class GestureSwipe : GestureImpl
+gtk.GestureSwipe           = {
+    ---@param button? integer
+    ---@return GestureSwipe
+    new = function(button) end,
+
+    -- Called at the end of a swipe with its velocity in pixels per second
+    ---@param self GestureSwipe
+    ---@param callback fun(velocity_x: number, velocity_y: number, button: integer):nil
+    connect_swipe = function(self, callback) end,
+}
+
+---@class GestureDrag : GestureImpl
+gtk.GestureDrag            = {
+    ---@param button? integer
+    ---@return GestureDrag
+    new = function(button) end,
+
+    ---@param self GestureDrag
+    ---@param callback fun(start_x: number, start_y: number, button: integer):nil
+    connect_drag_begin = function(self, callback) end,
+
+    -- Offset is relative to the start point
+    ---@param self GestureDrag
+    ---@param callback fun(offset_x: number, offset_y: number, button: integer):nil
+    connect_drag_update = function(self, callback) end,
+
+    ---@param self GestureDrag
+    ---@param callback fun(offset_x: number, offset_y: number, button: integer):nil
+    connect_drag_end = function(self, callback) end,
+
+    -- Returns nil if there's no ongoing drag
+    ---@param self GestureDrag
+    ---@return number? x
+    ---@return number? y
+    start_point = function(self) end,
+
+    ---@param self GestureDrag
+    ---@return number? x
+    ---@return number? y
+    offset = function(self) end,
+}
+
+---@class IconPaintable : ObjectImpl
 gtk.IconPaintable          = {
     ---@param file File
     ---@return IconPaintable
@@ -943,7 +2217,7 @@ gtk.IconPaintable          = {
     is_symbolic = function(self) end,
 }
 
----@class IconTheme
+---@class IconTheme : ObjectImpl
 gtk.IconTheme              = {
     ---@return IconTheme
     default = function() end,
@@ -982,7 +2256,7 @@ gtk.IconTheme              = {
     lookup_icon = function(self, icon_name, fallbacks, size, scale) end,
 }
 
----@class Settings
+---@class Settings : ObjectImpl
 gtk.Settings               = {
     ---@param self Settings
     ---@return string
@@ -1017,7 +2291,7 @@ gtk.Settings               = {
     set_gtk_icon_theme_name = function(self, icon_theme_name) end,
 }
 
----@class CssProvider
+---@class CssProvider : ObjectImpl
 gtk.CssProvider            = {
     ---@return CssProvider
     new = function() end,
@@ -1034,6 +2308,26 @@ gtk.CssProvider            = {
 ---@param provider CssProvider
 function gtk.style_context_add_provider(provider) end
 
+-- Any other key sets the property of the same name, converted like `set_property`.
+-- Properties are set after children are added, so they can refer to them, e.g. `visible_child_name`.
+-- Properties holding a widget can be set to a nested spec
+---@class WidgetSpec
+---@field type string Class name of the widget, e.g. `"Box"`
+---@field id string? Key of the widget in the returned ids table
+---@field children WidgetSpec[]? Appended to containers, or set as the `child` property. `CenterBox` doesn't take children, set `start_widget`, `center_widget` and `end_widget` instead
+---@field left integer? Column of a `Grid` child. 0 by default
+---@field top integer? Row of a `Grid` child. 0 by default
+---@field width integer? Number of columns a `Grid` child spans. 1 by default
+---@field height integer? Number of rows a `Grid` child spans. 1 by default
+---@field on table<string, fun(...):any>? Signal handlers, like `connect`
+---@field [string] any
+
+-- Builds a widget tree and returns its root, along with the widgets that have an `id`
+---@param spec WidgetSpec
+---@return WidgetImpl root
+---@return table<string, WidgetImpl> ids
+function gtk.build(spec) end
+
 gtk.layer_shell = {}
 
 ---@enum Layer
@@ -60,6 +60,12 @@ pulseaudio.Mainloop = {
 ---@field default_sink_name string
 ---@field default_source_name string
 
+---@class ModuleInfo
+---@field index integer
+---@field name string?
+---@field argument string?
+---@field n_used integer?
+
 ---@class InterestMaskSet
 ---@field sink boolean?
 ---@field source boolean?
@@ -131,7 +137,30 @@ pulseaudio.Context = {
     ---@param index integer
     ---@param volume integer[]
     ---@param callback? fun(success: boolean):nil
-    set_sink_volume_by_index = function(self, index, volume, callback) end
+    set_sink_volume_by_index = function(self, index, volume, callback) end,
+
+    -- Returns all currently loaded modules
+    ---@async
+    ---@param self Context
+    ---@return ModuleInfo[]
+    get_module_info_list = function(self) end,
+
+    -- Loads a module, returning its index.
+    -- Throws an error if the module failed to load
+    ---@async
+    ---@param self Context
+    ---@param name string
+    ---@param argument? string
+    ---@return integer
+    load_module = function(self, name, argument) end,
+
+    -- Unloads a module by its index.
+    -- Returns whether the module was unloaded
+    ---@async
+    ---@param self Context
+    ---@param index integer
+    ---@return boolean
+    unload_module = function(self, index) end
 }
 
 crabshell.pulseaudio = pulseaudio
@@ -31,6 +31,34 @@ local sysinfo = {}
 ---@field brand string
 ---@field cpu_usage number
 
+---@class CpuFreq
+---@field cpu integer Index of the core, as in `cpuN`
+---@field governor string?
+---@field available_governors string[]
+---@field governor_writable boolean
+---@field energy_performance_preference string?
+---@field available_energy_performance_preferences string[]
+---@field energy_performance_preference_writable boolean
+---@field frequency integer? Current frequency in kHz
+---@field min_frequency integer? In kHz
+---@field max_frequency integer? In kHz
+
+---@class PressureStats
+---@field avg10 number Percentage of time stalled over the last 10 seconds
+---@field avg60 number
+---@field avg300 number
+---@field total integer Total stall time in µs
+
+---@class Pressure
+---@field some PressureStats Time at least some tasks were stalled
+---@field full PressureStats? Time all non-idle tasks were stalled at once
+
+-- Resources are nil when the kernel doesn't support PSI
+---@class SystemPressure
+---@field cpu Pressure?
+---@field memory Pressure?
+---@field io Pressure?
+
 ---@class NetworkData
 ---@field received integer
 ---@field total_received integer
@@ -46,6 +74,83 @@ local sysinfo = {}
 ---@field total_errors_on_transmitted integer
 ---@field mac_address table<integer, integer>
 
+---@class LoadAvg
+---@field one number
+---@field five number
+---@field fifteen number
+
+---@class Disk
+---@field name string
+---@field mount_point string
+---@field file_system string
+---@field total_space integer Size in bytes
+---@field available_space integer Size in bytes
+---@field is_removable boolean
+
+---@class Component
+---@field label string
+---@field temperature number Temperature in °C
+---@field max number Highest temperature seen, in °C
+---@field critical number? Critical temperature in °C
+
+---@class User
+---@field name string
+---@field groups string[]
+
+---@class DiskUsage
+---@field total_written_bytes integer
+---@field written_bytes integer
+---@field total_read_bytes integer
+---@field read_bytes integer
+
+---@class Process
+---@field pid integer
+---@field name string
+---@field cmd string[]
+---@field exe string
+---@field cpu_usage number
+---@field memory integer Memory usage in bytes
+---@field status string
+---@field start_time integer Seconds since the epoch
+---@field parent integer?
+---@field disk_usage DiskUsage
+
+---@enum Signal
+sysinfo.Signal = {
+    Hangup = 1,
+    Interrupt = 2,
+    Quit = 3,
+    Illegal = 4,
+    Trap = 5,
+    Abort = 6,
+    IOT = 6,
+    Bus = 7,
+    FloatingPointException = 8,
+    Kill = 9,
+    User1 = 10,
+    Segv = 11,
+    User2 = 12,
+    Pipe = 13,
+    Alarm = 14,
+    Term = 15,
+    Child = 17,
+    Continue = 18,
+    Stop = 19,
+    TSTP = 20,
+    TTIN = 21,
+    TTOU = 22,
+    Urgent = 23,
+    XCPU = 24,
+    XFSZ = 25,
+    VirtualAlarm = 26,
+    Profiling = 27,
+    Winch = 28,
+    IO = 29,
+    Poll = 29,
+    Power = 30,
+    Sys = 31,
+}
+
 ---@class System
 sysinfo.System = {
     ---@return System
@@ -113,6 +218,29 @@ sysinfo.System = {
     ---@return Cpu
     global_cpu_info = function(self) end,
 
+    -- Reads cpufreq state of every core. Cores without cpufreq support are skipped,
+    -- and attributes that can't be read are left nil or empty
+    ---@param self System
+    ---@return CpuFreq[]
+    cpufreq = function(self) end,
+
+    -- Requires `governor_writable`
+    ---@param self System
+    ---@param governor string One of `available_governors`
+    ---@param cpu? integer Core index. Sets the governor of every core if nil
+    set_governor = function(self, governor, cpu) end,
+
+    -- Requires `energy_performance_preference_writable`
+    ---@param self System
+    ---@param preference string One of `available_energy_performance_preferences`
+    ---@param cpu? integer Core index. Sets the preference of every core if nil
+    set_energy_performance_preference = function(self, preference, cpu) end,
+
+    -- Reads Pressure Stall Information
+    ---@param self System
+    ---@return SystemPressure
+    pressure = function(self) end,
+
     ---@param self System
     ---@return integer
     total_memory = function(self) end,
@@ -140,18 +268,158 @@ sysinfo.System = {
     ---@param self System
     ---@return table<string, NetworkData>
     networks = function(self) end,
+
+    ---@param self System
+    ---@return LoadAvg
+    load_average = function(self) end,
+
+    -- Returns system uptime in seconds
+    ---@param self System
+    ---@return integer
+    uptime = function(self) end,
+
+    -- Returns system boot time in seconds since the epoch
+    ---@param self System
+    ---@return integer
+    boot_time = function(self) end,
+
+    ---@param self System
+    ---@return string?
+    host_name = function(self) end,
+
+    ---@param self System
+    ---@return string?
+    kernel_version = function(self) end,
+
+    ---@param self System
+    ---@return string?
+    os_version = function(self) end,
+
+    ---@param self System
+    ---@return string?
+    long_os_version = function(self) end,
+
+    ---@param self System
+    ---@return string
+    distribution_id = function(self) end,
+
+    ---@param self System
+    ---@return Disk[]
+    disks = function(self) end,
+
+    ---@param self System
+    ---@return Component[]
+    components = function(self) end,
+
+    ---@param self System
+    ---@return User[]
+    users = function(self) end,
+
+    ---@param self System
+    ---@return Process[]
+    processes = function(self) end,
+
+    ---@param self System
+    ---@param pid integer
+    ---@return Process?
+    process = function(self, pid) end,
+
+    -- Returns processes which names contain `name`
+    ---@param self System
+    ---@param name string
+    ---@return Process[]
+    processes_by_name = function(self, name) end,
+
+    -- Sends a signal to the process.
+    -- Returns false if the process doesn't exist or can't be signalled
+    ---@param self System
+    ---@param pid integer
+    ---@param signal? Signal|string POSIX signal number or `Signal` name. Kill by default
+    ---@return boolean
+    kill = function(self, pid, signal) end,
+}
+
+---@class NetworkRate
+---@field received number Bytes received per second
+---@field transmitted number Bytes transmitted per second
+
+---@class Sample
+---@field timestamp number Seconds since the epoch
+---@field cpu_usage number Usage of all cpus in percent
+---@field cpu_usages number[] Usage of each cpu in percent
+---@field used_memory integer
+---@field total_memory integer
+---@field memory_percent number
+---@field used_swap integer
+---@field total_swap integer
+---@field swap_percent number
+---@field networks table<string, NetworkRate>
+---@field network NetworkRate Sum of all network rates
+
+---@class SamplerOptions
+---@field interval number? Refresh interval in seconds. 1 by default
+---@field history integer? Number of samples to keep. 60 by default, 0 keeps none
+
+-- Refreshes system info on a separate thread and collects its history
+---@class Sampler
+sysinfo.Sampler = {
+    ---@param options? SamplerOptions
+    ---@return Sampler
+    new = function(options) end,
+
+    ---@param self Sampler
+    start = function(self) end,
+
+    ---@param self Sampler
+    stop = function(self) end,
+
+    ---@param self Sampler
+    ---@return boolean
+    running = function(self) end,
+
+    -- Called on the main context for every new sample
+    ---@param self Sampler
+    ---@param callback fun(sample: Sample)
+    connect_sample = function(self, callback) end,
+
+    -- Returns collected samples, from oldest to newest
+    ---@param self Sampler
+    ---@return Sample[]
+    history = function(self) end,
+
+    -- Returns the newest sample of the history, so always nil when no history is kept
+    ---@param self Sampler
+    ---@return Sample?
+    latest = function(self) end,
+
+    ---@param self Sampler
+    clear_history = function(self) end,
 }
 
 ---@class Duration
 ---@field secs integer
 ---@field nanos integer
 
+---@alias EnergyUnit
+---| "uWh" # Energy in µWh
+---| "uAh" # Charge in µAh
+
 ---@class BatteryInfo
----@field capacity integer
----@field full number
----@field now number
----@field current number
----@field remaining_time Duration
+---@field capacity integer?
+---@field capacity_level string?
+---@field unit EnergyUnit? Unit of `full`, `now` and `full_design`
+---@field full number?
+---@field full_design number?
+---@field health number? `full` to `full_design` ratio in percent
+---@field now number?
+---@field current number? Rate of `now` change, in `unit` per hour
+---@field power number? Power draw in watts
+---@field voltage_now number? Voltage in µV
+---@field remaining_time Duration?
+---@field cycle_count integer?
+---@field technology string?
+---@field manufacturer string?
+---@field model_name string?
 ---@field status string
 
 ---@class Batteries
@@ -159,12 +427,163 @@ sysinfo.System = {
 ---@field total_capacity integer
 ---@field remaining_time Duration
 
+---@class BatteryMonitorOptions
+---@field thresholds integer[]? Capacity thresholds to report crossings of
+---@field interval number? Polling interval in seconds, used when uevents are unavailable. 30 by default, at least 1
+
+-- Watches power supplies using kernel uevents, falling back to polling
+---@class BatteryMonitor
+local BatteryMonitor = {
+    ---@param options? BatteryMonitorOptions
+    ---@return BatteryMonitor
+    new = function(options) end,
+
+    ---@param self BatteryMonitor
+    ---@param force_polling? boolean Don't try to listen to uevents
+    start = function(self, force_polling) end,
+
+    ---@param self BatteryMonitor
+    stop = function(self) end,
+
+    -- Re-reads power supplies immediately, emitting signals for any changes
+    ---@param self BatteryMonitor
+    refresh = function(self) end,
+
+    ---@param self BatteryMonitor
+    ---@return boolean
+    running = function(self) end,
+
+    -- Returns whether the monitor listens to uevents instead of polling
+    ---@param self BatteryMonitor
+    ---@return boolean
+    uses_uevent = function(self) end,
+
+    ---@param self BatteryMonitor
+    ---@param thresholds integer[]
+    set_thresholds = function(self, thresholds) end,
+
+    -- Called when the AC adapter gets plugged or unplugged
+    ---@param self BatteryMonitor
+    ---@param callback fun(online: boolean)
+    connect_ac_changed = function(self, callback) end,
+
+    -- Called when a battery gets added or removed
+    ---@param self BatteryMonitor
+    ---@param callback fun(name: string, present: boolean)
+    connect_battery_changed = function(self, callback) end,
+
+    ---@param self BatteryMonitor
+    ---@param callback fun(name: string, status: string, previous: string)
+    connect_status_changed = function(self, callback) end,
+
+    -- Called when battery capacity goes below (`falling`) or above a threshold
+    ---@param self BatteryMonitor
+    ---@param callback fun(name: string, threshold: integer, capacity: integer, falling: boolean)
+    connect_threshold_crossed = function(self, callback) end,
+}
+
 sysinfo.battery = {
+    BatteryMonitor = BatteryMonitor,
+
     ---@return boolean
     is_on_ac = function() end,
 
+    -- Fields that aren't provided by the device, or can't be read, are left nil
     ---@return Batteries
     get_batteries = function() end
 }
 
+-- Temperatures are in °C
+---@class Temperature
+---@field label string?
+---@field input number?
+---@field max number?
+---@field critical number?
+
+-- Speeds are in RPM
+---@class Fan
+---@field label string?
+---@field input integer?
+---@field min integer?
+---@field max integer?
+
+---@class HwmonDevice
+---@field id string Directory name, e.g. `hwmon0`
+---@field name string? Driver name, e.g. `coretemp`
+---@field temperatures Temperature[]
+---@field fans Fan[]
+
+---@class ThermalZone
+---@field id string Directory name, e.g. `thermal_zone0`
+---@field type string?
+---@field temperature number? Temperature in °C
+---@field critical number? Critical trip point in °C
+
+---@class Sensors
+---@field hwmon HwmonDevice[]
+---@field thermal ThermalZone[]
+
+sysinfo.sensors = {
+    -- Reads hwmon devices and thermal zones.
+    -- Fields that aren't provided by the device, or can't be read, are left nil
+    ---@return Sensors
+    get_sensors = function() end
+}
+
+---@class BacklightInfo
+---@field name string
+---@field type string? `raw`, `platform` or `firmware`
+---@field brightness integer 0 if it can't be read
+---@field max_brightness integer 0 if it can't be read
+---@field actual_brightness integer? Brightness reported by the hardware, nil if it can't be read
+---@field percent number `brightness` to `max_brightness` ratio in percent
+
+---@class BacklightMonitorOptions
+---@field interval number? Polling interval in seconds. 1 by default
+
+-- Polls backlights, reporting brightness changes
+---@class BacklightMonitor
+local BacklightMonitor = {
+    ---@param options? BacklightMonitorOptions
+    ---@return BacklightMonitor
+    new = function(options) end,
+
+    ---@param self BacklightMonitor
+    start = function(self) end,
+
+    ---@param self BacklightMonitor
+    stop = function(self) end,
+
+    -- Re-reads backlights immediately, emitting signals for any changes
+    ---@param self BacklightMonitor
+    refresh = function(self) end,
+
+    ---@param self BacklightMonitor
+    ---@return boolean
+    running = function(self) end,
+
+    -- Called when brightness of a backlight changes
+    ---@param self BacklightMonitor
+    ---@param callback fun(info: BacklightInfo)
+    connect_changed = function(self, callback) end,
+}
+
+sysinfo.backlight = {
+    BacklightMonitor = BacklightMonitor,
+
+    ---@return BacklightInfo[]
+    get_backlights = function() end,
+
+    ---@param name string
+    ---@return BacklightInfo
+    get_backlight = function(name) end,
+
+    -- Sets raw brightness, clamped to `max_brightness`.
+    -- Uses logind when available, falling back to writing to sysfs directly
+    ---@async
+    ---@param name string
+    ---@param value integer
+    set_brightness = function(name, value) end,
+}
+
 crabshell.sysinfo = sysinfo
@@ -87,6 +87,31 @@ utils.json = {
     ---@param str string
     ---@return table
     from_str = function(str) end,
+
+    -- Parsed JSON that stays unconverted until `to_lua` is called.
+    -- Can be sent to workers
+    ---@class JsonValue
+    JsonValue = {
+        ---@param str string
+        ---@return JsonValue
+        from_str = function(str) end,
+
+        ---@param value any
+        ---@return JsonValue
+        from_lua = function(value) end,
+
+        ---@param self JsonValue
+        ---@return any
+        to_lua = function(self) end,
+
+        ---@param self JsonValue
+        ---@return string
+        to_string = function(self) end,
+
+        ---@param self JsonValue
+        ---@return string
+        to_string_pretty = function(self) end,
+    },
 }
 
 crabshell.utils = utils
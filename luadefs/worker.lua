@@ -2,6 +2,9 @@
 ---@diagnostic disable:lowercase-global
 ---@diagnostic disable:unused-local
 
+-- Workers run on their own threads, so they only get APIs that are safe to use off the main thread:
+-- `gio` (except `AppInfoMonitor`, menus, actions and `ListStore`), `glib` (except `MainContext`, `Value` and `Binding`), `sysinfo` (except `Sampler` and monitors), `utf8` and `utils`.
+-- Accessing `gdk`, `gtk`, `hyprland`, `pulseaudio` or any of the excluded types throws an error
 local worker = {}
 
 ---@type WorkerSenderSlave?
@@ -10,7 +13,9 @@ worker.sender = nil
 ---@type WorkerSenderSlave?
 worker.receiver = nil
 
----@alias WorkerData nil | boolean | integer | number | string | Texture | WorkerData[]
+-- Tables may be nested and have any keys, but can't contain cycles.
+-- Userdata is copied, keeping the original usable
+---@alias WorkerData nil | boolean | integer | number | string | Texture | Bytes | File | JsonValue | table<any, WorkerData>
 
 -- A channel that sends `WorkerData` to the worker
 ---@class WorkerSenderMaster
@@ -37,20 +42,20 @@ local WorkerSenderMaster = {
 ---@class WorkerReceiverMaster
 local WorkerReceiverMaster = {
     -- Receives data from worker's channel.
-    -- Propagates any error that was generated.
-    -- Returns nil if worker has stopped
+    -- Propagates any error that was generated, including the worker's name and traceback.
+    -- Throws an error if worker has finished
     ---@async
     ---@param self WorkerReceiverMaster
-    ---@return WorkerData?
+    ---@return WorkerData
     recv = function(self) end,
 
     --- A blocking version of `recv`
     ---@param self WorkerReceiverMaster
-    ---@return WorkerData?
+    ---@return WorkerData
     recv_blocking = function(self) end,
 
     -- Receives data from worker's channel.
-    -- Propagates any error that was generated.
+    -- Propagates any error that was generated. Throws an error if worker has finished.
     -- Returns false,nil if no data is available
     ---@param self WorkerReceiverMaster
     ---@return boolean
@@ -103,30 +108,124 @@ local WorkerReceiverSlave = {
     try_recv = function(self) end
 }
 
+-- Receives changes of values in `worker.shared`, made after it has been created.
+-- Changes are skipped if the subscription falls more than 64 changes behind,
+-- `worker.shared.get` always returns the latest value
+---@class SharedSubscription
+local SharedSubscription = {
+    -- Waits for the next change
+    ---@async
+    ---@param self SharedSubscription
+    ---@return string key
+    ---@return WorkerData value New value, nil if the key has been removed
+    recv = function(self) end,
+
+    -- A blocking version of `recv`
+    ---@param self SharedSubscription
+    ---@return string key
+    ---@return WorkerData value
+    recv_blocking = function(self) end,
+
+    -- Returns false if there are no pending changes
+    ---@param self SharedSubscription
+    ---@return boolean
+    ---@return string? key
+    ---@return WorkerData value
+    try_recv = function(self) end,
+}
+
+-- Key-value store shared by the main lua vm and every worker.
+-- Values are copied on every access, following the same rules as `WorkerData`
+worker.shared = {
+    ---@param key string
+    ---@return WorkerData
+    get = function(key) end,
+
+    -- Setting nil removes the key
+    ---@param key string
+    ---@param value WorkerData
+    set = function(key, value) end,
+
+    -- Sets `value` only if the current value equals `expected`, which is nil for missing keys.
+    -- Tables are compared by contents, userdata never compares equal.
+    -- Returns false and the current value if it doesn't
+    ---@param key string
+    ---@param expected WorkerData
+    ---@param value WorkerData
+    ---@return boolean
+    ---@return WorkerData current
+    compare_and_swap = function(key, expected, value) end,
+
+    -- Subscribes to changes of `key`, or of every key if it's nil
+    ---@param key? string
+    ---@return SharedSubscription
+    subscribe = function(key) end,
+}
+
+---@alias WorkerStatus
+---| "running"
+---| "finished" # Code has returned
+---| "terminated" # `terminate` has been called
+---| "failed" # Code has thrown an error
+
 ---@class Worker
 worker.Worker = {
+    -- Starts a worker in a new lua vm, which inherits `package.path`,
+    -- `package.cpath` and bytecode cache settings of the current one.
+    -- The JIT compiler is turned off in the worker, see `terminate`
     ---@param code string Code to be passed to the created lua vm
-    ---@param name? string Compiled chunk name
+    ---@param name? string Thread and compiled chunk name. "worker" by default
     ---@param channel_size? integer Size of the worker mpmpc channels. 32 By default
+    ---@param ... WorkerData Arguments passed to the code as `...`
+    ---@return Worker
+    start = function(code, name, channel_size, ...) end,
+
+    -- Same as `start`, but runs a file. Uses the bytecode cache if it's enabled
+    ---@param path string
+    ---@param ... WorkerData Arguments passed to the file as `...`
     ---@return Worker
-    start = function(code, name, channel_size) end,
+    start_file = function(path, ...) end,
+
+    -- Same as `start_file`, but finds the file using `package.path`, as `require` does
+    ---@param name string Module name, e.g. `widgets.launcher`
+    ---@param ... WorkerData Arguments passed to the module as `...`
+    ---@return Worker
+    start_module = function(name, ...) end,
+
+    ---@param self Worker
+    ---@return string
+    name = function(self) end,
 
     -- Returns whether worker has terminated
     ---@param self Worker
     ---@return boolean
     dead = function(self) end,
 
-    -- Waits for worker termination, returning immediately if it's dead.
-    -- Returns all collected data in a table. Propagates any error that was generated.
+    ---@param self Worker
+    ---@return WorkerStatus
+    status = function(self) end,
+
+    -- Asks the worker to stop. It's interrupted within the next 1000 executed instructions,
+    -- or when it waits on any of its channels. Workers run with the JIT compiler turned off
+    -- for this, but a long call into native code, e.g. a blocking read, has to return first.
+    -- Data that has already been sent can still be received
+    ---@param self Worker
+    terminate = function(self) end,
+
+    -- Waits for worker termination, returning all collected data in a table.
+    -- Propagates any error that was generated, including the worker's name and traceback.
+    -- Returns nil if `timeout` has elapsed, keeping collected data for the next call
     ---@async
     ---@param self Worker
+    ---@param timeout? number Timeout in seconds
     ---@return WorkerData[]?
-    join = function(self) end,
+    join = function(self, timeout) end,
 
     -- A blocking version of `join`
     ---@param self Worker
+    ---@param timeout? number Timeout in seconds
     ---@return WorkerData[]?
-    join_blocking = function(self) end,
+    join_blocking = function(self, timeout) end,
 
     ---@param self Worker
     ---@return WorkerSenderMaster
@@ -137,4 +236,111 @@ worker.Worker = {
     receiver = function(self) end,
 }
 
+-- Pending result of a task submitted to a `WorkerPool`
+---@class PoolTask
+local PoolTask = {
+    -- Returns whether the result is available without waiting
+    ---@param self PoolTask
+    ---@return boolean
+    done = function(self) end,
+
+    -- Waits for the task to finish, returning values returned by the function.
+    -- Propagates any error that was generated, including the pool's name and traceback.
+    -- Can only be called once
+    ---@async
+    ---@param self PoolTask
+    ---@return WorkerData ...
+    result = function(self) end,
+
+    -- A blocking version of `result`
+    ---@param self PoolTask
+    ---@return WorkerData ...
+    result_blocking = function(self) end,
+}
+
+-- A fixed set of threads, each running the same code in its own lua vm.
+-- The vms are reused by all tasks, which makes pools suitable for short jobs.
+-- Code is run once per thread and should return a table of functions
+-- that tasks can call. Globals are used if it returns nothing
+---@class WorkerPool
+worker.WorkerPool = {
+    -- Starts a pool, which inherits `package.path`, `package.cpath`
+    -- and bytecode cache settings of the current lua vm
+    ---@param code string
+    ---@param size? integer Number of threads. Number of CPUs by default
+    ---@param queue_size? integer Number of tasks that can wait for a free thread. 32 By default
+    ---@param name? string Thread and compiled chunk name. "worker-pool" by default
+    ---@return WorkerPool
+    new = function(code, size, queue_size, name) end,
+
+    -- Same as `new`, but runs a file. Uses the bytecode cache if it's enabled
+    ---@param path string
+    ---@param size? integer
+    ---@param queue_size? integer
+    ---@return WorkerPool
+    from_file = function(path, size, queue_size) end,
+
+    -- Same as `from_file`, but finds the file using `package.path`, as `require` does
+    ---@param name string Module name, e.g. `launcher.fuzzy`
+    ---@param size? integer
+    ---@param queue_size? integer
+    ---@return WorkerPool
+    from_module = function(name, size, queue_size) end,
+
+    ---@param self WorkerPool
+    ---@return string
+    name = function(self) end,
+
+    -- Returns the number of threads
+    ---@param self WorkerPool
+    ---@return integer
+    size = function(self) end,
+
+    -- Returns the number of tasks waiting for a free thread
+    ---@param self WorkerPool
+    ---@return integer
+    queued = function(self) end,
+
+    -- Queues a call of `function` with the given arguments.
+    -- Waits for space in the queue if it's full.
+    -- Throws an error if the pool has been closed
+    ---@async
+    ---@param self WorkerPool
+    ---@param func string Name of the function
+    ---@param ... WorkerData
+    ---@return PoolTask
+    submit = function(self, func, ...) end,
+
+    -- A blocking version of `submit`
+    ---@param self WorkerPool
+    ---@param func string
+    ---@param ... WorkerData
+    ---@return PoolTask
+    submit_blocking = function(self, func, ...) end,
+
+    -- Same as `submit`, but returns nil if the queue is full
+    ---@param self WorkerPool
+    ---@param func string
+    ---@param ... WorkerData
+    ---@return PoolTask?
+    try_submit = function(self, func, ...) end,
+
+    -- Submits a task and waits for its result
+    ---@async
+    ---@param self WorkerPool
+    ---@param func string
+    ---@param ... WorkerData
+    ---@return WorkerData ...
+    call = function(self, func, ...) end,
+
+    -- Stops accepting tasks. Threads exit after finishing queued ones.
+    -- Happens automatically when the pool is garbage collected
+    ---@param self WorkerPool
+    close = function(self) end,
+
+    ---@param self WorkerPool
+    ---@return boolean
+    closed = function(self) end,
+}
+
 crabshell.worker = worker
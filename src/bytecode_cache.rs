@@ -0,0 +1,109 @@
+use mlua::prelude::*;
+use std::{
+    collections::hash_map::DefaultHasher,
+    fs,
+    hash::{Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::error::Error;
+
+pub const CACHE_PATH: &str = "/tmp/crabshell";
+
+/// Makes temporary cache file names unique within the process
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Caches compiled chunks in `/tmp/crabshell`, keyed by their source path
+#[derive(Debug, Clone, Copy)]
+pub struct BytecodeCache {
+    /// Whether to write stripped bytecode
+    pub strip: bool,
+}
+
+impl BytecodeCache {
+    pub fn cache_path(path: &Path) -> PathBuf {
+        let mut cache_path = PathBuf::from(CACHE_PATH);
+
+        let mut hasher = DefaultHasher::new();
+        path.hash(&mut hasher);
+        let path_hash = hasher.finish();
+        let path_hash = format!("{:016x}.bc", path_hash);
+        cache_path.push(path_hash);
+
+        cache_path
+    }
+
+    /// Returns the cached bytecode if it was compiled from source with the given hash.
+    /// Files that can't be read or are too short to hold the hash count as missing
+    fn read(cache_path: &Path, source_hash: u64) -> Option<Vec<u8>> {
+        let mut data = fs::read(cache_path).ok()?;
+        if data.len() < 8 {
+            return None;
+        }
+
+        let hash = u64::from_le_bytes(data[..8].try_into().ok()?);
+        if hash != source_hash {
+            return None;
+        }
+
+        data.drain(..8);
+        Some(data)
+    }
+
+    fn write(cache_path: &Path, source_hash: u64, bytecode: &[u8]) -> Result<(), Error> {
+        fs::create_dir_all(CACHE_PATH)?;
+
+        // Workers can compile the same file at the same time, so write to a unique file and move it
+        // into place. Readers then see either the previous or the new file, never a partial one
+        let temp_path = cache_path.with_extension(format!(
+            "{}-{}.tmp",
+            process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let result = (|| {
+            let mut file = fs::File::create(&temp_path)?;
+            file.write_all(&source_hash.to_le_bytes())?;
+            file.write_all(bytecode)?;
+            fs::rename(&temp_path, cache_path)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+
+        Ok(result?)
+    }
+
+    /// Loads cached bytecode of the file at `path`, compiling and caching `source` if it has changed.
+    /// Cache files that can't be read or loaded are replaced as if they were outdated
+    pub fn load<'lua>(
+        &self,
+        lua: &'lua Lua,
+        path: &Path,
+        source: String,
+        name: &str,
+    ) -> Result<LuaFunction<'lua>, Error> {
+        let cache_path = Self::cache_path(path);
+
+        let mut hasher = DefaultHasher::new();
+        source.hash(&mut hasher);
+        let source_hash = hasher.finish();
+
+        // Load cached bytecode
+        if let Some(bytecode) = Self::read(&cache_path, source_hash) {
+            if let Ok(func) = lua.load(bytecode).set_name(name).into_function() {
+                return Ok(func);
+            }
+        }
+
+        // Compile source and cache its bytecode
+        let func = lua.load(source).set_name(name).into_function()?;
+        Self::write(&cache_path, source_hash, &func.dump(self.strip))?;
+
+        Ok(func)
+    }
+}
@@ -331,3 +331,188 @@ impl Into<i32> for RevealerTransitionType {
 }
 
 impl_lua!(RevealerTransitionType);
+
+pub(super) struct StackTransitionType(pub(super) gtk::StackTransitionType);
+impl std::convert::TryFrom<i32> for StackTransitionType {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self(gtk::StackTransitionType::None)),
+            1 => Ok(Self(gtk::StackTransitionType::Crossfade)),
+            2 => Ok(Self(gtk::StackTransitionType::SlideRight)),
+            3 => Ok(Self(gtk::StackTransitionType::SlideLeft)),
+            4 => Ok(Self(gtk::StackTransitionType::SlideUp)),
+            5 => Ok(Self(gtk::StackTransitionType::SlideDown)),
+            6 => Ok(Self(gtk::StackTransitionType::SlideLeftRight)),
+            7 => Ok(Self(gtk::StackTransitionType::SlideUpDown)),
+            8 => Ok(Self(gtk::StackTransitionType::OverUp)),
+            9 => Ok(Self(gtk::StackTransitionType::OverDown)),
+            10 => Ok(Self(gtk::StackTransitionType::OverLeft)),
+            11 => Ok(Self(gtk::StackTransitionType::OverRight)),
+            12 => Ok(Self(gtk::StackTransitionType::UnderUp)),
+            13 => Ok(Self(gtk::StackTransitionType::UnderDown)),
+            14 => Ok(Self(gtk::StackTransitionType::UnderLeft)),
+            15 => Ok(Self(gtk::StackTransitionType::UnderRight)),
+            16 => Ok(Self(gtk::StackTransitionType::OverUpDown)),
+            17 => Ok(Self(gtk::StackTransitionType::OverDownUp)),
+            18 => Ok(Self(gtk::StackTransitionType::OverLeftRight)),
+            19 => Ok(Self(gtk::StackTransitionType::OverRightLeft)),
+            20 => Ok(Self(gtk::StackTransitionType::RotateLeft)),
+            21 => Ok(Self(gtk::StackTransitionType::RotateRight)),
+            22 => Ok(Self(gtk::StackTransitionType::RotateLeftRight)),
+            _ => Err(Error::ConversionFailed),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<i32> for StackTransitionType {
+    fn into(self) -> i32 {
+        match self.0 {
+            gtk::StackTransitionType::None => 0,
+            gtk::StackTransitionType::Crossfade => 1,
+            gtk::StackTransitionType::SlideRight => 2,
+            gtk::StackTransitionType::SlideLeft => 3,
+            gtk::StackTransitionType::SlideUp => 4,
+            gtk::StackTransitionType::SlideDown => 5,
+            gtk::StackTransitionType::SlideLeftRight => 6,
+            gtk::StackTransitionType::SlideUpDown => 7,
+            gtk::StackTransitionType::OverUp => 8,
+            gtk::StackTransitionType::OverDown => 9,
+            gtk::StackTransitionType::OverLeft => 10,
+            gtk::StackTransitionType::OverRight => 11,
+            gtk::StackTransitionType::UnderUp => 12,
+            gtk::StackTransitionType::UnderDown => 13,
+            gtk::StackTransitionType::UnderLeft => 14,
+            gtk::StackTransitionType::UnderRight => 15,
+            gtk::StackTransitionType::OverUpDown => 16,
+            gtk::StackTransitionType::OverDownUp => 17,
+            gtk::StackTransitionType::OverLeftRight => 18,
+            gtk::StackTransitionType::OverRightLeft => 19,
+            gtk::StackTransitionType::RotateLeft => 20,
+            gtk::StackTransitionType::RotateRight => 21,
+            gtk::StackTransitionType::RotateLeftRight => 22,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl_lua!(StackTransitionType);
+
+pub(super) struct PositionType(pub(super) gtk::PositionType);
+impl std::convert::TryFrom<i32> for PositionType {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self(gtk::PositionType::Left)),
+            1 => Ok(Self(gtk::PositionType::Right)),
+            2 => Ok(Self(gtk::PositionType::Top)),
+            3 => Ok(Self(gtk::PositionType::Bottom)),
+            _ => Err(Error::ConversionFailed),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<i32> for PositionType {
+    fn into(self) -> i32 {
+        match self.0 {
+            gtk::PositionType::Left => 0,
+            gtk::PositionType::Right => 1,
+            gtk::PositionType::Top => 2,
+            gtk::PositionType::Bottom => 3,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl_lua!(PositionType);
+
+pub(super) struct PolicyType(pub(super) gtk::PolicyType);
+impl std::convert::TryFrom<i32> for PolicyType {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self(gtk::PolicyType::Always)),
+            1 => Ok(Self(gtk::PolicyType::Automatic)),
+            2 => Ok(Self(gtk::PolicyType::Never)),
+            3 => Ok(Self(gtk::PolicyType::External)),
+            _ => Err(Error::ConversionFailed),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<i32> for PolicyType {
+    fn into(self) -> i32 {
+        match self.0 {
+            gtk::PolicyType::Always => 0,
+            gtk::PolicyType::Automatic => 1,
+            gtk::PolicyType::Never => 2,
+            gtk::PolicyType::External => 3,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl_lua!(PolicyType);
+
+pub(super) struct SelectionMode(pub(super) gtk::SelectionMode);
+impl std::convert::TryFrom<i32> for SelectionMode {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self(gtk::SelectionMode::None)),
+            1 => Ok(Self(gtk::SelectionMode::Single)),
+            2 => Ok(Self(gtk::SelectionMode::Browse)),
+            3 => Ok(Self(gtk::SelectionMode::Multiple)),
+            _ => Err(Error::ConversionFailed),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<i32> for SelectionMode {
+    fn into(self) -> i32 {
+        match self.0 {
+            gtk::SelectionMode::None => 0,
+            gtk::SelectionMode::Single => 1,
+            gtk::SelectionMode::Browse => 2,
+            gtk::SelectionMode::Multiple => 3,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl_lua!(SelectionMode);
+
+pub(super) struct ArrowType(pub(super) gtk::ArrowType);
+impl std::convert::TryFrom<i32> for ArrowType {
+    type Error = Error;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self(gtk::ArrowType::Up)),
+            1 => Ok(Self(gtk::ArrowType::Down)),
+            2 => Ok(Self(gtk::ArrowType::Left)),
+            3 => Ok(Self(gtk::ArrowType::Right)),
+            4 => Ok(Self(gtk::ArrowType::None)),
+            _ => Err(Error::ConversionFailed),
+        }
+    }
+}
+
+#[allow(clippy::from_over_into)]
+impl Into<i32> for ArrowType {
+    fn into(self) -> i32 {
+        match self.0 {
+            gtk::ArrowType::Up => 0,
+            gtk::ArrowType::Down => 1,
+            gtk::ArrowType::Left => 2,
+            gtk::ArrowType::Right => 3,
+            gtk::ArrowType::None => 4,
+            _ => unreachable!(),
+        }
+    }
+}
+
+impl_lua!(ArrowType);
@@ -1,12 +1,15 @@
 use gtk::{gdk::Texture, gio::File, glib::Bytes};
 use mlua::prelude::*;
 
+use super::glib::add_object_methods;
 use crate::traits::LuaApi;
 
 impl LuaApi for Texture {
     const CLASS_NAME: &'static str = "Texture";
 
-    fn register_methods(_reg: &mut LuaUserDataRegistry<Self>) {}
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        add_object_methods(reg);
+    }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
         table.set(
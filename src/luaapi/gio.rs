@@ -3,10 +3,11 @@ use gtk::{
     gdk::AppLaunchContext,
     gio::{
         prelude::*, AppInfo, AppInfoMonitor, File, FileCreateFlags, Icon, InputStream,
-        InputStreamAsyncBufRead, OutputStream, SocketClient, SocketConnection, Subprocess,
-        ThemedIcon, UnixSocketAddress,
+        InputStreamAsyncBufRead, ListModel, ListStore, Menu, MenuItem, OutputStream, SimpleAction,
+        SimpleActionGroup, SocketClient, SocketConnection, Subprocess, ThemedIcon,
+        UnixSocketAddress,
     },
-    glib::{Bytes, GString, Priority},
+    glib::{BoxedAnyObject, Bytes, GString, Priority, VariantTy},
 };
 use mlua::prelude::*;
 use paste::paste;
@@ -15,7 +16,12 @@ use std::{ffi::OsStr, path::Path};
 use crate::utils::catch_lua_errors;
 use crate::{macros::register_signals, traits::LuaApi};
 
-use super::wrappers::SubprocessFlagsWrapper;
+use super::{
+    forbid_access,
+    glib::add_object_methods,
+    gtk::add_model_methods,
+    wrappers::{model_item, variant_from_lua, OwnedValue, SubprocessFlagsWrapper, VariantWrapper},
+};
 
 impl LuaApi for InputStreamAsyncBufRead<InputStream> {
     const CLASS_NAME: &'static str = "InputStreamAsyncBufRead<InputStream>";
@@ -81,6 +87,8 @@ impl LuaApi for InputStream {
             this.close_future(Priority::DEFAULT).await.into_lua_err()?;
             Ok(())
         });
+
+        add_object_methods(reg);
     }
 }
 
@@ -110,6 +118,8 @@ impl LuaApi for OutputStream {
             this.close_future(Priority::DEFAULT).await.into_lua_err()?;
             Ok(())
         });
+
+        add_object_methods(reg);
     }
 }
 
@@ -201,6 +211,8 @@ impl LuaApi for Subprocess {
                 Ok(None)
             }
         });
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -237,6 +249,8 @@ impl LuaApi for SocketConnection {
         reg.add_async_method("close", |_, this, ()| async move {
             this.close_future(Priority::DEFAULT).await.into_lua_err()
         });
+
+        add_object_methods(reg);
     }
 }
 
@@ -249,6 +263,8 @@ impl LuaApi for SocketClient {
             let conn = this.connect_future(&address).await.into_lua_err()?;
             lua.create_any_userdata(conn)
         });
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -302,6 +318,8 @@ impl LuaApi for File {
                 .into_lua_err()?;
             lua.create_any_userdata(stream.upcast::<OutputStream>())
         });
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -327,6 +345,8 @@ impl LuaApi for Icon {
                 },
             )
         });
+
+        add_object_methods(reg);
     }
 }
 
@@ -341,7 +361,9 @@ impl LuaApi for ThemedIcon {
         reg.add_method("names", |lua, this, ()| {
             let names = this.names();
             lua.create_sequence_from(names.iter().map(GString::as_str))
-        })
+        });
+
+        add_object_methods(reg);
     }
 }
 
@@ -350,6 +372,8 @@ impl LuaApi for AppInfoMonitor {
 
     fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
         register_signals!(reg, [changed]);
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -430,6 +454,8 @@ impl LuaApi for AppInfo {
         reg.add_method("supports_files", |_, this, ()| Ok(this.supports_files()));
         reg.add_method("supports_uris", |_, this, ()| Ok(this.supports_uris()));
         reg.add_method("can_delete", |_, this, ()| Ok(this.can_delete()));
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -447,19 +473,352 @@ impl LuaApi for AppInfo {
     }
 }
 
+impl LuaApi for Menu {
+    const CLASS_NAME: &'static str = "Menu";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "append",
+            |_, this, (label, action): (Option<String>, Option<String>)| {
+                this.append(label.as_deref(), action.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "prepend",
+            |_, this, (label, action): (Option<String>, Option<String>)| {
+                this.prepend(label.as_deref(), action.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "insert",
+            |_, this, (position, label, action): (i32, Option<String>, Option<String>)| {
+                this.insert(position, label.as_deref(), action.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "append_section",
+            |_, this, (label, section): (Option<String>, LuaUserDataRef<Menu>)| {
+                this.append_section(label.as_deref(), &*section);
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "append_submenu",
+            |_, this, (label, submenu): (Option<String>, LuaUserDataRef<Menu>)| {
+                this.append_submenu(label.as_deref(), &*submenu);
+                Ok(())
+            },
+        );
+
+        // Placeholder for a widget added with `PopoverMenu:add_child`
+        reg.add_method("append_custom", |_, this, id: String| {
+            let item = MenuItem::new(None, None);
+            item.set_attribute_value("custom", Some(&id.to_variant()));
+            this.append_item(&item);
+            Ok(())
+        });
+
+        reg.add_method("remove", |_, this, position: i32| {
+            this.remove(position);
+            Ok(())
+        });
+
+        reg.add_method("remove_all", |_, this, ()| {
+            this.remove_all();
+            Ok(())
+        });
+
+        reg.add_method("n_items", |_, this, ()| Ok(this.n_items()));
+
+        add_object_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| lua.create_any_userdata(Menu::new()))?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for SimpleAction {
+    const CLASS_NAME: &'static str = "SimpleAction";
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!("SimpleAction {{ name = \"{}\" }}", self.name()))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("name", |lua, this, ()| {
+            lua.create_string(this.name().as_str())
+        });
+
+        reg.add_method("set_enabled", |_, this, enabled: bool| {
+            this.set_enabled(enabled);
+            Ok(())
+        });
+
+        reg.add_method("is_enabled", |_, this, ()| Ok(this.is_enabled()));
+
+        reg.add_method("set_state", |_, this, state: LuaValue| {
+            let state_type = this.state_type().ok_or_else(|| {
+                LuaError::RuntimeError(format!("Action `{}` is stateless", this.name()))
+            })?;
+            this.set_state(&variant_from_lua(&state, &state_type)?);
+            Ok(())
+        });
+
+        reg.add_method("state", |_, this, ()| Ok(this.state().map(VariantWrapper)));
+
+        reg.add_method("activate", |_, this, parameter: LuaValue| {
+            let parameter = match (this.parameter_type(), parameter) {
+                (None, LuaValue::Nil) => None,
+                (None, _) => {
+                    return Err(LuaError::RuntimeError(format!(
+                        "Action `{}` doesn't take a parameter",
+                        this.name()
+                    )))
+                }
+                (Some(parameter_type), parameter) => {
+                    Some(variant_from_lua(&parameter, &parameter_type)?)
+                }
+            };
+            this.activate(parameter.as_ref());
+            Ok(())
+        });
+
+        reg.add_method("connect_activate", |_, this, f: LuaOwnedFunction| {
+            this.connect_activate(move |_, parameter| {
+                let parameter = parameter.cloned().map(VariantWrapper);
+                catch_lua_errors::<_, ()>(f.to_ref(), parameter);
+            });
+            Ok(())
+        });
+
+        // Replaces the default handler, so the callback has to call `set_state` itself
+        reg.add_method("connect_change_state", |_, this, f: LuaOwnedFunction| {
+            this.connect_change_state(move |_, value| {
+                let value = value.cloned().map(VariantWrapper);
+                catch_lua_errors::<_, ()>(f.to_ref(), value);
+            });
+            Ok(())
+        });
+
+        add_object_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, (name, parameter_type): (String, Option<String>)| {
+                let parameter_type = parameter_type
+                    .as_deref()
+                    .map(VariantTy::new)
+                    .transpose()
+                    .into_lua_err()?;
+                lua.create_any_userdata(SimpleAction::new(&name, parameter_type))
+            })?,
+        )?;
+
+        table.set(
+            "new_stateful",
+            lua.create_function(
+                |lua,
+                 (name, state, parameter_type, state_type): (
+                    String,
+                    LuaValue,
+                    Option<String>,
+                    Option<String>,
+                )| {
+                    let parameter_type = parameter_type
+                        .as_deref()
+                        .map(VariantTy::new)
+                        .transpose()
+                        .into_lua_err()?;
+                    let state_type = state_type
+                        .as_deref()
+                        .map(VariantTy::new)
+                        .transpose()
+                        .into_lua_err()?
+                        .or(parameter_type);
+                    let state = match state_type {
+                        Some(state_type) => variant_from_lua(&state, state_type)?,
+                        None => VariantWrapper::from_lua(state, lua)?.0,
+                    };
+                    let action = SimpleAction::new_stateful(&name, parameter_type, &state);
+                    lua.create_any_userdata(action)
+                },
+            )?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for SimpleActionGroup {
+    const CLASS_NAME: &'static str = "SimpleActionGroup";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "add_action",
+            |_, this, action: LuaUserDataRef<SimpleAction>| {
+                this.add_action(&*action);
+                Ok(())
+            },
+        );
+
+        reg.add_method("remove_action", |_, this, name: String| {
+            this.remove_action(&name);
+            Ok(())
+        });
+
+        add_object_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| lua.create_any_userdata(SimpleActionGroup::new()))?,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Checks that `position..position + count` lies within `store`
+fn check_range(store: &ListStore, position: u32, count: u32) -> LuaResult<()> {
+    let n_items = store.n_items();
+    match position.checked_add(count) {
+        Some(end) if end <= n_items => Ok(()),
+        _ => Err(LuaError::RuntimeError(format!(
+            "Range {position}..{} is out of bounds of a ListStore with {n_items} items",
+            position as u64 + count as u64
+        ))),
+    }
+}
+
+impl LuaApi for ListStore {
+    const CLASS_NAME: &'static str = "ListStore";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("upcast", |lua, this, ()| {
+            lua.create_any_userdata(this.clone().upcast::<ListModel>())
+        });
+
+        reg.add_method("append", |_, this, value: OwnedValue| {
+            this.append(&model_item(value));
+            Ok(())
+        });
+
+        reg.add_method("insert", |_, this, (position, value): (u32, OwnedValue)| {
+            check_range(this, position, 0)?;
+            this.insert(position, &model_item(value));
+            Ok(())
+        });
+
+        reg.add_method("set", |_, this, (position, value): (u32, OwnedValue)| {
+            check_range(this, position, 1)?;
+            this.splice(position, 1, &[model_item(value)]);
+            Ok(())
+        });
+
+        reg.add_method("remove", |_, this, position: u32| {
+            check_range(this, position, 1)?;
+            this.remove(position);
+            Ok(())
+        });
+
+        reg.add_method("remove_all", |_, this, ()| {
+            this.remove_all();
+            Ok(())
+        });
+
+        reg.add_method(
+            "splice",
+            |_, this, (position, n_removals, values): (u32, u32, Vec<OwnedValue>)| {
+                check_range(this, position, n_removals)?;
+                let items = values.into_iter().map(model_item).collect::<Vec<_>>();
+                this.splice(position, n_removals, &items);
+                Ok(())
+            },
+        );
+
+        add_model_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, values: Option<Vec<OwnedValue>>| {
+                let store = ListStore::new::<BoxedAnyObject>();
+                let items = values
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(model_item)
+                    .collect::<Vec<_>>();
+                store.extend_from_slice(&items);
+                lua.create_any_userdata(store)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn push_thread_safe_types(lua: &Lua, gio_table: &LuaTable) -> LuaResult<()> {
+    InputStreamAsyncBufRead::<InputStream>::push_lua(lua, gio_table)?;
+    InputStream::push_lua(lua, gio_table)?;
+    OutputStream::push_lua(lua, gio_table)?;
+    Subprocess::push_lua(lua, gio_table)?;
+    SocketConnection::push_lua(lua, gio_table)?;
+    SocketClient::push_lua(lua, gio_table)?;
+    File::push_lua(lua, gio_table)?;
+    Icon::push_lua(lua, gio_table)?;
+    ThemedIcon::push_lua(lua, gio_table)?;
+    AppInfo::push_lua(lua, gio_table)?;
+
+    Ok(())
+}
+
 pub fn push_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
     let gio_table = lua.create_table()?;
-    InputStreamAsyncBufRead::<InputStream>::push_lua(lua, &gio_table)?;
-    InputStream::push_lua(lua, &gio_table)?;
-    OutputStream::push_lua(lua, &gio_table)?;
-    Subprocess::push_lua(lua, &gio_table)?;
-    SocketConnection::push_lua(lua, &gio_table)?;
-    SocketClient::push_lua(lua, &gio_table)?;
-    File::push_lua(lua, &gio_table)?;
-    Icon::push_lua(lua, &gio_table)?;
-    ThemedIcon::push_lua(lua, &gio_table)?;
+    push_thread_safe_types(lua, &gio_table)?;
     AppInfoMonitor::push_lua(lua, &gio_table)?;
-    AppInfo::push_lua(lua, &gio_table)?;
+    Menu::push_lua(lua, &gio_table)?;
+    SimpleAction::push_lua(lua, &gio_table)?;
+    SimpleActionGroup::push_lua(lua, &gio_table)?;
+    ListStore::push_lua(lua, &gio_table)?;
+    table.set("gio", gio_table)?;
+
+    Ok(())
+}
+
+/// Same as `push_api`, but without `AppInfoMonitor`, which emits signals on the main loop,
+/// and menus, actions and list stores, which are only useful with widgets
+pub fn push_thread_safe_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+    let gio_table = lua.create_table()?;
+    push_thread_safe_types(lua, &gio_table)?;
+    forbid_access(
+        lua,
+        &gio_table,
+        "crabshell.gio",
+        Some(&[
+            "AppInfoMonitor",
+            "ListStore",
+            "Menu",
+            "SimpleAction",
+            "SimpleActionGroup",
+        ]),
+    )?;
     table.set("gio", gio_table)?;
 
     Ok(())
@@ -1,8 +1,155 @@
-use gtk::glib::{self, Bytes, MainContext, Value};
-use mlua::prelude::*;
+use gtk::glib::{
+    self, prelude::*, subclass::signal::SignalId, Binding, Bytes, MainContext, ParamFlags,
+    ParamSpec, Value,
+};
+use mlua::{prelude::*, Variadic};
 use paste::paste;
 
-use crate::{traits::LuaApi, utils::catch_lua_errors_async};
+use super::{
+    forbid_access,
+    wrappers::{object_from_userdata, value_from_lua, BindingFlagsWrapper, ValueWrapper},
+};
+use crate::{
+    error::LuaErrorWrapper,
+    traits::LuaApi,
+    utils::{catch_lua_errors, catch_lua_errors_async},
+};
+
+fn find_property(
+    object: &impl glib::IsA<glib::Object>,
+    property_name: &str,
+) -> LuaResult<ParamSpec> {
+    object.find_property(property_name).ok_or_else(|| {
+        LuaError::RuntimeError(format!(
+            "Property `{}` does not exist on `{}`",
+            property_name,
+            object.type_().name()
+        ))
+    })
+}
+
+/// Checks that the property exists and can be set after construction
+pub(super) fn writable_property(
+    object: &impl glib::IsA<glib::Object>,
+    property_name: &str,
+) -> LuaResult<ParamSpec> {
+    let pspec = find_property(object, property_name)?;
+    let flags = pspec.flags();
+    if !flags.contains(ParamFlags::WRITABLE) || flags.contains(ParamFlags::CONSTRUCT_ONLY) {
+        return Err(LuaError::RuntimeError(format!(
+            "Property `{}` of `{}` is not writable",
+            property_name,
+            object.type_().name()
+        )));
+    }
+
+    Ok(pspec)
+}
+
+/// Connects `f` to any signal, converting its arguments and return value
+pub(super) fn connect_lua_signal(
+    object: &impl glib::IsA<glib::Object>,
+    signal_name: &str,
+    f: LuaOwnedFunction,
+) -> LuaResult<glib::SignalHandlerId> {
+    let Some((signal_id, _)) = SignalId::parse_name(signal_name, object.type_(), false) else {
+        return Err(LuaError::RuntimeError(format!(
+            "Signal `{}` does not exist on `{}`",
+            signal_name,
+            object.type_().name()
+        )));
+    };
+    let return_type = signal_id.query().return_type().type_();
+
+    Ok(object.connect_local(signal_name, false, move |values| {
+        let args = values[1..]
+            .iter()
+            .map(|value| ValueWrapper(value.clone()))
+            .collect::<Variadic<_>>();
+        let ret = catch_lua_errors::<_, LuaValue>(f.to_ref(), args);
+
+        if return_type == glib::Type::UNIT {
+            return None;
+        }
+
+        Some(match ret {
+            Some(LuaValue::Nil) | None => Value::from_type(return_type),
+            Some(ret) => value_from_lua(&ret, return_type).unwrap_or_else(|err| {
+                eprintln!(
+                    "Invalid signal handler return value:\n{}",
+                    LuaErrorWrapper(err)
+                );
+                Value::from_type(return_type)
+            }),
+        })
+    }))
+}
+
+/// Methods available on every object: properties, generic signal connection and property binding
+pub(super) fn add_object_methods<T: glib::IsA<glib::Object>>(reg: &mut LuaUserDataRegistry<'_, T>) {
+    reg.add_method("as_object", |lua, this, ()| {
+        lua.create_any_userdata(this.clone().upcast::<glib::Object>())
+    });
+
+    reg.add_method(
+        "set_property",
+        |_, this, (property_name, value): (String, LuaValue)| {
+            let pspec = writable_property(this, &property_name)?;
+            let value = value_from_lua(&value, pspec.value_type())?;
+            this.set_property_from_value(&property_name, &value);
+            Ok(())
+        },
+    );
+
+    reg.add_method("property", |_, this, property_name: String| {
+        let pspec = find_property(this, &property_name)?;
+        if !pspec.flags().contains(ParamFlags::READABLE) {
+            return Err(LuaError::RuntimeError(format!(
+                "Property `{}` of `{}` is not readable",
+                property_name,
+                this.type_().name()
+            )));
+        }
+
+        Ok(ValueWrapper(this.property_value(&property_name)))
+    });
+
+    reg.add_method(
+        "connect",
+        |lua, this, (signal_name, f): (String, LuaOwnedFunction)| {
+            lua.create_any_userdata(connect_lua_signal(this, &signal_name, f)?)
+        },
+    );
+
+    reg.add_method("disconnect", |_, this, handler_id: LuaAnyUserData| {
+        this.disconnect(handler_id.take::<glib::SignalHandlerId>()?);
+        Ok(())
+    });
+
+    reg.add_method(
+        "bind_property",
+        |lua,
+         this,
+         (source_property, target, target_property, flags): (
+            String,
+            LuaAnyUserData,
+            String,
+            BindingFlagsWrapper,
+        )| {
+            let target = object_from_userdata(&target).ok_or_else(|| {
+                LuaError::RuntimeError(String::from("Binding target is not an object"))
+            })?;
+            find_property(this, &source_property)?;
+            find_property(&target, &target_property)?;
+
+            let binding = this
+                .bind_property(&source_property, &target, &target_property)
+                .flags(flags.0)
+                .build();
+            lua.create_any_userdata(binding)
+        },
+    );
+}
 
 fn push_constants(lua: &Lua, glib_table: &LuaTable) -> LuaResult<()> {
     let priority = lua.create_table()?;
@@ -121,6 +268,58 @@ impl LuaApi for Value {
     }
 }
 
+impl LuaApi for Bytes {
+    const CLASS_NAME: &'static str = "Bytes";
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!("Bytes {{ len = {} }}", self.len()))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_meta_method(LuaMetaMethod::Len, |_, this, ()| Ok(this.len()));
+        reg.add_method("len", |_, this, ()| Ok(this.len()));
+        reg.add_method("to_str", |lua, this, ()| lua.create_string(&**this));
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, data: LuaString| {
+                lua.create_any_userdata(Bytes::from(data.as_bytes()))
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for glib::Object {
+    const CLASS_NAME: &'static str = "Object";
+    const CONSTRUCTIBLE: bool = false;
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!("Object {{ type = \"{}\" }}", self.type_().name()))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        add_object_methods(reg);
+    }
+}
+
+impl LuaApi for Binding {
+    const CLASS_NAME: &'static str = "Binding";
+    const CONSTRUCTIBLE: bool = false;
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("unbind", |_, this, ()| {
+            this.unbind();
+            Ok(())
+        });
+
+        add_object_methods(reg);
+    }
+}
+
 impl LuaApi for MainContext {
     const CLASS_NAME: &'static str = "MainContext";
 
@@ -173,13 +372,38 @@ impl LuaApi for MainContext {
     }
 }
 
+fn push_thread_safe_types(lua: &Lua, glib_table: &LuaTable) -> LuaResult<()> {
+    push_constants(lua, glib_table)?;
+    Bytes::push_lua(lua, glib_table)?;
+
+    Ok(())
+}
+
 pub fn push_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
     let glib_table = lua.create_table()?;
 
-    push_constants(lua, &glib_table)?;
+    push_thread_safe_types(lua, &glib_table)?;
     Value::push_lua(lua, &glib_table)?;
     MainContext::push_lua(lua, &glib_table)?;
+    glib::Object::push_lua(lua, &glib_table)?;
+    Binding::push_lua(lua, &glib_table)?;
+
+    table.set("glib", glib_table)?;
+
+    Ok(())
+}
 
+/// Leaves out types tied to the main thread: values and bindings may hold its objects,
+/// and the main context can only be used by the thread that owns it
+pub fn push_thread_safe_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+    let glib_table = lua.create_table()?;
+    push_thread_safe_types(lua, &glib_table)?;
+    forbid_access(
+        lua,
+        &glib_table,
+        "crabshell.glib",
+        Some(&["Binding", "MainContext", "Value"]),
+    )?;
     table.set("glib", glib_table)?;
 
     Ok(())
@@ -1,23 +1,32 @@
 use gtk::{
     cairo, gdk,
-    gio::Icon,
-    glib::{self, GString, Value},
+    gio::{self, Icon},
+    glib::{self, GString},
     pango,
     prelude::*,
-    Application, ApplicationWindow, Box, Button, CenterBox, CheckButton, CssProvider, DrawingArea,
-    Entry, EntryBuffer, EventControllerFocus, EventControllerKey, EventControllerMotion,
-    EventControllerScroll, Grid, IconLookupFlags, IconPaintable, IconTheme, Image, Label, Overlay,
-    Revealer, Scale, Settings, ToggleButton,
+    Application, ApplicationWindow, Box, Button, CenterBox, CheckButton, CssProvider, CustomFilter,
+    CustomSorter, DrawingArea, Entry, EntryBuffer, EventControllerFocus, EventControllerKey,
+    EventControllerMotion, EventControllerScroll, FilterChange, FilterListModel, FlowBox,
+    FlowBoxChild, GestureClick, GestureDrag, GestureLongPress, GestureSwipe, Grid, GridView,
+    IconLookupFlags, IconPaintable, IconTheme, Image, Label, ListBox, ListBoxRow, ListItem,
+    ListView, MenuButton, MultiSelection, NoSelection, Notebook, Overlay, Popover, PopoverMenu,
+    Revealer, Scale, ScrolledWindow, Settings, SignalListItemFactory, SingleSelection,
+    SortListModel, SorterChange, Stack, StackSidebar, StackSwitcher, ToggleButton,
 };
-use gtk4_layer_shell::LayerShell;
+use gtk4_layer_shell::{KeyboardMode, LayerShell};
 use mlua::prelude::*;
 use paste::paste;
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
 use super::{
     enums,
+    glib::{add_object_methods, connect_lua_signal, writable_property},
     wrappers::{
-        ApplicationFlagsWrapper, ContextWrapper, EventControllerScrollFlagsWrapper, GStringWrapper,
-        ModifierTypeWrapper, RGBAWrapper,
+        value_from_lua, ApplicationFlagsWrapper, ContextWrapper, EventControllerScrollFlagsWrapper,
+        GStringWrapper, ModelItemWrapper, ModifierTypeWrapper, RGBAWrapper, UserDataWrapper,
     },
 };
 use crate::{macros::register_signals, traits::LuaApi, utils::catch_lua_errors};
@@ -30,22 +39,14 @@ macro_rules! push_enum {
     };
 }
 
-fn add_widget_methods<T: glib::IsA<gtk::Widget>>(reg: &mut LuaUserDataRegistry<'_, T>) {
+fn add_widget_methods<T: glib::IsA<gtk::Widget> + glib::IsA<glib::Object>>(
+    reg: &mut LuaUserDataRegistry<'_, T>,
+) {
     reg.add_method("upcast", |lua, this, ()| {
         lua.create_any_userdata(this.clone().upcast::<gtk::Widget>())
     });
 
-    reg.add_method(
-        "set_property",
-        |_, this, (property_name, value): (String, LuaUserDataRef<Value>)| {
-            this.set_property_from_value(&property_name, &value);
-            Ok(())
-        },
-    );
-
-    reg.add_method("property", |lua, this, property_name: String| {
-        lua.create_any_userdata(this.property_value(&property_name))
-    });
+    add_object_methods(reg);
 
     reg.add_method(
         "add_controller",
@@ -170,6 +171,14 @@ fn add_widget_methods<T: glib::IsA<gtk::Widget>>(reg: &mut LuaUserDataRegistry<'
         |_, this, ()| Ok(this.allocated_height()),
     );
 
+    reg.add_method(
+        "insert_action_group",
+        |_, this, (prefix, group): (String, Option<LuaUserDataRef<gio::SimpleActionGroup>>)| {
+            this.insert_action_group(&prefix, group.as_deref());
+            Ok(())
+        },
+    );
+
     reg.add_method(
         "set_layout_manager",
         |_, this, layout_manager: Option<LuaOwnedAnyUserData>| {
@@ -252,9 +261,265 @@ fn push_enums(lua: &Lua, gtk_table: &LuaTable) -> LuaResult<()> {
         ]
     );
 
+    push_enum!(
+        lua,
+        gtk_table,
+        gtk,
+        StackTransitionType,
+        [
+            None,
+            Crossfade,
+            SlideRight,
+            SlideLeft,
+            SlideUp,
+            SlideDown,
+            SlideLeftRight,
+            SlideUpDown,
+            OverUp,
+            OverDown,
+            OverLeft,
+            OverRight,
+            UnderUp,
+            UnderDown,
+            UnderLeft,
+            UnderRight,
+            OverUpDown,
+            OverDownUp,
+            OverLeftRight,
+            OverRightLeft,
+            RotateLeft,
+            RotateRight,
+            RotateLeftRight
+        ]
+    );
+
+    push_enum!(
+        lua,
+        gtk_table,
+        gtk,
+        PositionType,
+        [Left, Right, Top, Bottom]
+    );
+
+    push_enum!(
+        lua,
+        gtk_table,
+        gtk,
+        ArrowType,
+        [Up, Down, Left, Right, None]
+    );
+
+    push_enum!(
+        lua,
+        gtk_table,
+        gtk,
+        PolicyType,
+        [Always, Automatic, Never, External]
+    );
+
+    push_enum!(
+        lua,
+        gtk_table,
+        gtk,
+        SelectionMode,
+        [None, Single, Browse, Multiple]
+    );
+
+    Ok(())
+}
+
+/// Applies the setup that Lua constructors do on top of plain object construction
+fn init_widget(widget: &gtk::Widget) {
+    if let Some(popover) = widget.downcast_ref::<Popover>() {
+        allow_popover_focus(popover);
+    } else if let Some(menu_button) = widget.downcast_ref::<MenuButton>() {
+        allow_menu_button_focus(menu_button);
+    }
+}
+
+/// Creates a widget from its class name and returns it along with its userdata
+fn new_widget<'lua>(
+    lua: &'lua Lua,
+    type_name: &str,
+) -> LuaResult<(gtk::Widget, LuaAnyUserData<'lua>)> {
+    macro_rules! new_widget {
+        ([$($typ:ident),+]) => {
+            match type_name {
+                $(stringify!($typ) => {
+                    let widget = glib::Object::new::<$typ>();
+                    init_widget(widget.upcast_ref());
+                    Ok((widget.clone().upcast(), lua.create_any_userdata(widget)?))
+                })+
+                _ => Err(LuaError::RuntimeError(format!(
+                    "Unknown widget type `{}`",
+                    type_name
+                ))),
+            }
+        };
+    }
+
+    new_widget!([
+        Box,
+        Grid,
+        CenterBox,
+        Overlay,
+        Label,
+        Entry,
+        Button,
+        ToggleButton,
+        CheckButton,
+        DrawingArea,
+        Image,
+        Scale,
+        Revealer,
+        Stack,
+        StackSwitcher,
+        StackSidebar,
+        Notebook,
+        ScrolledWindow,
+        ListBox,
+        ListBoxRow,
+        FlowBox,
+        FlowBoxChild,
+        ListView,
+        GridView,
+        Popover,
+        PopoverMenu,
+        MenuButton
+    ])
+}
+
+/// Adds `child` to a container, or sets it as the `child` property of single child widgets
+fn append_child(parent: &gtk::Widget, child: &gtk::Widget) -> LuaResult<()> {
+    if let Some(parent) = parent.downcast_ref::<Box>() {
+        parent.append(child);
+    } else if let Some(parent) = parent.downcast_ref::<ListBox>() {
+        parent.append(child);
+    } else if let Some(parent) = parent.downcast_ref::<FlowBox>() {
+        parent.append(child);
+    } else if let Some(parent) = parent.downcast_ref::<Stack>() {
+        parent.add_child(child);
+    } else if let Some(parent) = parent.downcast_ref::<Notebook>() {
+        parent.append_page(child, None::<&gtk::Widget>);
+    } else if let Some(parent) = parent.downcast_ref::<Overlay>() {
+        if parent.child().is_none() {
+            parent.set_child(Some(child));
+        } else {
+            parent.add_overlay(child);
+        }
+    } else if parent.is::<CenterBox>() {
+        return Err(LuaError::RuntimeError(String::from(
+            "`GtkCenterBox` doesn't take `children`, \
+             set `start_widget`, `center_widget` and `end_widget` instead",
+        )));
+    } else if parent
+        .find_property("child")
+        .is_some_and(|pspec| child.type_().is_a(pspec.value_type()))
+    {
+        if parent.property::<Option<gtk::Widget>>("child").is_some() {
+            return Err(LuaError::RuntimeError(format!(
+                "`{}` can only hold one child",
+                parent.type_().name()
+            )));
+        }
+
+        writable_property(parent, "child")?;
+        parent.set_property("child", child);
+    } else {
+        return Err(LuaError::RuntimeError(format!(
+            "`{}` doesn't take `children`",
+            parent.type_().name()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Keys of child specs that place them in a `Grid`
+const GRID_LAYOUT_KEYS: [&str; 4] = ["left", "top", "width", "height"];
+
+/// Attaches `child` to the cells given by the layout keys of its spec.
+/// The first column and row and a single cell are used by default
+fn attach_grid_child(grid: &Grid, child: &gtk::Widget, spec: &LuaTable) -> LuaResult<()> {
+    let left = spec.get::<_, Option<i32>>("left")?.unwrap_or(0);
+    let top = spec.get::<_, Option<i32>>("top")?.unwrap_or(0);
+    let width = spec.get::<_, Option<i32>>("width")?.unwrap_or(1);
+    let height = spec.get::<_, Option<i32>>("height")?.unwrap_or(1);
+    if width < 1 || height < 1 {
+        return Err(LuaError::RuntimeError(String::from(
+            "`width` and `height` of grid children must be at least 1",
+        )));
+    }
+
+    grid.attach(child, left, top, width, height);
     Ok(())
 }
 
+/// Builds a widget tree from `spec`, adding widgets that have an `id` to `ids`.
+/// Specs of grid children may also have layout keys
+fn build_widget<'lua>(
+    lua: &'lua Lua,
+    spec: LuaTable<'lua>,
+    ids: &LuaTable<'lua>,
+    grid_child: bool,
+) -> LuaResult<(gtk::Widget, LuaAnyUserData<'lua>)> {
+    let type_name = spec.get::<_, String>("type")?;
+    let (widget, ud) = new_widget(lua, &type_name)?;
+
+    // Children go first, so that properties like `visible_child_name` can refer to them
+    if let Some(children) = spec.get::<_, Option<LuaTable>>("children")? {
+        let grid = widget.downcast_ref::<Grid>();
+        for child_spec in children.sequence_values::<LuaTable>() {
+            let child_spec = child_spec?;
+            let (child, _) = build_widget(lua, child_spec.clone(), ids, grid.is_some())?;
+            match grid {
+                Some(grid) => attach_grid_child(grid, &child, &child_spec)?,
+                None => append_child(&widget, &child)?,
+            }
+        }
+    }
+
+    for pair in spec.clone().pairs::<String, LuaValue>() {
+        let (key, value) = pair?;
+        if matches!(key.as_str(), "type" | "id" | "children" | "on")
+            || (grid_child && GRID_LAYOUT_KEYS.contains(&key.as_str()))
+        {
+            continue;
+        }
+
+        let pspec = writable_property(&widget, &key)?;
+        let value = match value {
+            LuaValue::Table(child_spec) if child_spec.contains_key("type")? => {
+                let (child, _) = build_widget(lua, child_spec, ids, false)?;
+                if !child.type_().is_a(pspec.value_type()) {
+                    return Err(LuaError::RuntimeError(format!(
+                        "Property `{}` of `{}` can't be set to `{}`",
+                        key,
+                        type_name,
+                        child.type_().name()
+                    )));
+                }
+                child.to_value()
+            }
+            value => value_from_lua(&value, pspec.value_type())?,
+        };
+        widget.set_property_from_value(&key, &value);
+    }
+
+    if let Some(signals) = spec.get::<_, Option<LuaTable>>("on")? {
+        for pair in signals.pairs::<String, LuaOwnedFunction>() {
+            let (signal_name, f) = pair?;
+            connect_lua_signal(&widget, &signal_name, f)?;
+        }
+    }
+
+    if let Some(id) = spec.get::<_, Option<String>>("id")? {
+        ids.set(id, ud.clone())?;
+    }
+
+    Ok((widget, ud))
+}
+
 fn add_global_functions(lua: &Lua, gtk_table: &LuaTable) -> LuaResult<()> {
     gtk_table.set(
         "style_context_add_provider",
@@ -270,6 +535,15 @@ fn add_global_functions(lua: &Lua, gtk_table: &LuaTable) -> LuaResult<()> {
         })?,
     )?;
 
+    gtk_table.set(
+        "build",
+        lua.create_function(|lua, spec: LuaTable| {
+            let ids = lua.create_table()?;
+            let (_, root) = build_widget(lua, spec, &ids, false)?;
+            Ok((root, ids))
+        })?,
+    )?;
+
     Ok(())
 }
 
@@ -283,6 +557,21 @@ impl LuaApi for Application {
             this.run_with_args(&[""]);
             Ok(())
         });
+
+        reg.add_method(
+            "add_action",
+            |_, this, action: LuaUserDataRef<gio::SimpleAction>| {
+                this.add_action(&*action);
+                Ok(())
+            },
+        );
+
+        reg.add_method("remove_action", |_, this, name: String| {
+            this.remove_action(&name);
+            Ok(())
+        });
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -327,6 +616,19 @@ impl LuaApi for ApplicationWindow {
             Ok(())
         });
 
+        reg.add_method(
+            "add_action",
+            |_, this, action: LuaUserDataRef<gio::SimpleAction>| {
+                this.add_action(&*action);
+                Ok(())
+            },
+        );
+
+        reg.add_method("remove_action", |_, this, name: String| {
+            this.remove_action(&name);
+            Ok(())
+        });
+
         add_widget_methods(reg);
     }
 
@@ -642,7 +944,9 @@ impl LuaApi for gtk::EntryBuffer {
         reg.add_method("set_text", |_, this, chars: String| {
             this.set_text(chars);
             Ok(())
-        })
+        });
+
+        add_object_methods(reg);
     }
 }
 
@@ -1281,107 +1585,151 @@ impl LuaApi for Revealer {
     }
 }
 
-impl LuaApi for EventControllerKey {
-    const CLASS_NAME: &'static str = "EventControllerKey";
+impl LuaApi for Stack {
+    const CLASS_NAME: &'static str = "Stack";
 
     fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
-        reg.add_method("upcast", |lua, this, ()| {
-            lua.create_any_userdata(this.clone().upcast::<gtk::EventController>())
-        });
+        register_signals!(reg, [visible_child_notify]);
 
-        reg.add_method("forward", |_, this, widget: LuaUserDataRef<gtk::Widget>| {
-            Ok(this.forward(&*widget))
-        });
+        reg.add_method(
+            "add_child",
+            |_, this, child: LuaUserDataRef<gtk::Widget>| {
+                this.add_child(&*child);
+                Ok(())
+            },
+        );
 
-        reg.add_method("connect_key_pressed", |_, this, f: LuaOwnedFunction| {
-            this.connect_key_pressed(move |_, key, keycode, state| {
-                let key_name = key.name().map(GStringWrapper);
-                let state = ModifierTypeWrapper(state);
-                let result = f
-                    .call::<_, Option<bool>>((key_name, keycode, state))
-                    .unwrap();
+        reg.add_method(
+            "add_named",
+            |_, this, (child, name): (LuaUserDataRef<gtk::Widget>, Option<String>)| {
+                this.add_named(&*child, name.as_deref());
+                Ok(())
+            },
+        );
 
-                if result.unwrap_or(false) {
-                    glib::Propagation::Stop
-                } else {
-                    glib::Propagation::Proceed
-                }
-            });
+        reg.add_method(
+            "add_titled",
+            |_,
+             this,
+             (child, name, title): (LuaUserDataRef<gtk::Widget>, Option<String>, String)| {
+                this.add_titled(&*child, name.as_deref(), &title);
+                Ok(())
+            },
+        );
 
+        reg.add_method("remove", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.remove(&*child);
             Ok(())
         });
 
-        reg.add_method("connect_key_released", |_, this, f: LuaOwnedFunction| {
-            this.connect_key_pressed(move |_, key, keycode, state| {
-                let key_name = key.name().map(GStringWrapper);
-                let state = ModifierTypeWrapper(state);
-                let result = f
-                    .call::<_, Option<bool>>((key_name, keycode, state))
-                    .unwrap();
+        reg.add_method("child_by_name", |lua, this, name: String| {
+            this.child_by_name(&name)
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
 
-                if result.unwrap_or(false) {
-                    glib::Propagation::Stop
-                } else {
-                    glib::Propagation::Proceed
+        reg.add_method(
+            "set_visible_child",
+            |_, this, child: LuaUserDataRef<gtk::Widget>| {
+                this.set_visible_child(&*child);
+                Ok(())
+            },
+        );
+
+        reg.add_method("visible_child", |lua, this, ()| {
+            this.visible_child()
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
+
+        reg.add_method(
+            "set_visible_child_name",
+            |_, this, (name, transition): (String, Option<enums::StackTransitionType>)| {
+                match transition {
+                    Some(transition) => this.set_visible_child_full(&name, transition.0),
+                    None => this.set_visible_child_name(&name),
                 }
-            });
+                Ok(())
+            },
+        );
 
-            Ok(())
+        reg.add_method("visible_child_name", |_, this, ()| {
+            Ok(this.visible_child_name().map(GStringWrapper))
         });
-    }
 
-    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
-        table.set(
-            "new",
-            lua.create_function(|lua, ()| {
-                let event_controller = EventControllerKey::new();
-                lua.create_any_userdata(event_controller)
-            })?,
-        )?;
+        reg.add_method(
+            "set_page_title",
+            |_, this, (child, title): (LuaUserDataRef<gtk::Widget>, String)| {
+                this.page(&*child).set_title(&title);
+                Ok(())
+            },
+        );
 
-        Ok(())
-    }
-}
+        reg.add_method(
+            "set_page_icon_name",
+            |_, this, (child, icon_name): (LuaUserDataRef<gtk::Widget>, String)| {
+                this.page(&*child).set_icon_name(&icon_name);
+                Ok(())
+            },
+        );
 
-impl LuaApi for EventControllerScroll {
-    const CLASS_NAME: &'static str = "EventControllerScroll";
+        reg.add_method(
+            "set_page_needs_attention",
+            |_, this, (child, needs_attention): (LuaUserDataRef<gtk::Widget>, bool)| {
+                this.page(&*child).set_needs_attention(needs_attention);
+                Ok(())
+            },
+        );
 
-    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
-        register_signals!(reg, [scroll_begin, scroll_end]);
+        reg.add_method(
+            "set_transition_type",
+            |_, this, transition: enums::StackTransitionType| {
+                this.set_transition_type(transition.0);
+                Ok(())
+            },
+        );
 
-        reg.add_method("upcast", |lua, this, ()| {
-            lua.create_any_userdata(this.clone().upcast::<gtk::EventController>())
+        reg.add_method("transition_type", |_, this, ()| {
+            Ok(enums::StackTransitionType(this.transition_type()))
         });
 
-        reg.add_method("connect_scroll", |_, this, f: LuaOwnedFunction| {
-            this.connect_scroll(move |_, dx, dy| {
-                let result =
-                    catch_lua_errors::<_, Option<bool>>(f.to_ref(), (dx, dy)).unwrap_or(None);
-                if result.unwrap_or(false) {
-                    glib::Propagation::Stop
-                } else {
-                    glib::Propagation::Proceed
-                }
-            });
+        reg.add_method("set_transition_duration", |_, this, duration: u32| {
+            this.set_transition_duration(duration);
+            Ok(())
+        });
+
+        reg.add_method("transition_duration", |_, this, ()| {
+            Ok(this.transition_duration())
+        });
+
+        reg.add_method("is_transition_running", |_, this, ()| {
+            Ok(this.is_transition_running())
+        });
 
+        reg.add_method("set_hhomogeneous", |_, this, homogeneous: bool| {
+            this.set_hhomogeneous(homogeneous);
             Ok(())
         });
 
-        reg.add_method("connect_decelerate", |_, this, f: LuaOwnedFunction| {
-            this.connect_decelerate(move |_, vel_x, vel_y| {
-                catch_lua_errors::<_, ()>(f.to_ref(), (vel_x, vel_y));
-            });
+        reg.add_method("set_vhomogeneous", |_, this, homogeneous: bool| {
+            this.set_vhomogeneous(homogeneous);
+            Ok(())
+        });
 
+        reg.add_method("set_interpolate_size", |_, this, interpolate_size: bool| {
+            this.set_interpolate_size(interpolate_size);
             Ok(())
         });
+
+        add_widget_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
         table.set(
             "new",
-            lua.create_function(|lua, flags: EventControllerScrollFlagsWrapper| {
-                let event_controller = EventControllerScroll::new(flags.0);
-                lua.create_any_userdata(event_controller)
+            lua.create_function(|lua, ()| {
+                let stack = Stack::new();
+                lua.create_any_userdata(stack)
             })?,
         )?;
 
@@ -1389,39 +1737,28 @@ impl LuaApi for EventControllerScroll {
     }
 }
 
-impl LuaApi for EventControllerMotion {
-    const CLASS_NAME: &'static str = "EventControllerMotion";
+impl LuaApi for StackSwitcher {
+    const CLASS_NAME: &'static str = "StackSwitcher";
 
     fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
-        register_signals!(reg, [leave]);
-
-        reg.add_method("upcast", |lua, this, ()| {
-            lua.create_any_userdata(this.clone().upcast::<gtk::EventController>())
-        });
-
-        reg.add_method("connect_enter", |_, this, f: LuaOwnedFunction| {
-            this.connect_enter(move |_, x, y| {
-                catch_lua_errors::<_, ()>(f.to_ref(), (x, y));
-            });
-
-            Ok(())
-        });
-
-        reg.add_method("connect_motion", |_, this, f: LuaOwnedFunction| {
-            this.connect_motion(move |_, x, y| {
-                catch_lua_errors::<_, ()>(f.to_ref(), (x, y));
-            });
+        reg.add_method(
+            "set_stack",
+            |_, this, stack: Option<LuaUserDataRef<Stack>>| {
+                this.set_stack(stack.as_deref());
+                Ok(())
+            },
+        );
 
-            Ok(())
-        });
+        add_widget_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
         table.set(
             "new",
-            lua.create_function(|lua, ()| {
-                let event_controller = EventControllerMotion::new();
-                lua.create_any_userdata(event_controller)
+            lua.create_function(|lua, stack: Option<LuaUserDataRef<Stack>>| {
+                let switcher = StackSwitcher::new();
+                switcher.set_stack(stack.as_deref());
+                lua.create_any_userdata(switcher)
             })?,
         )?;
 
@@ -1429,17 +1766,1759 @@ impl LuaApi for EventControllerMotion {
     }
 }
 
-impl LuaApi for EventControllerFocus {
-    const CLASS_NAME: &'static str = "EventControllerFocus";
+impl LuaApi for StackSidebar {
+    const CLASS_NAME: &'static str = "StackSidebar";
 
     fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
-        register_signals!(reg, [enter, leave]);
+        reg.add_method("set_stack", |_, this, stack: LuaUserDataRef<Stack>| {
+            this.set_stack(&stack);
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, stack: Option<LuaUserDataRef<Stack>>| {
+                let sidebar = StackSidebar::new();
+                if let Some(stack) = stack {
+                    sidebar.set_stack(&stack);
+                }
+                lua.create_any_userdata(sidebar)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for Notebook {
+    const CLASS_NAME: &'static str = "Notebook";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "append_page",
+            |_,
+             this,
+             (child, tab_label): (
+                LuaUserDataRef<gtk::Widget>,
+                Option<LuaUserDataRef<gtk::Widget>>,
+            )| { Ok(this.append_page(&*child, tab_label.as_deref())) },
+        );
+
+        reg.add_method(
+            "prepend_page",
+            |_,
+             this,
+             (child, tab_label): (
+                LuaUserDataRef<gtk::Widget>,
+                Option<LuaUserDataRef<gtk::Widget>>,
+            )| { Ok(this.prepend_page(&*child, tab_label.as_deref())) },
+        );
+
+        reg.add_method(
+            "insert_page",
+            |_,
+             this,
+             (child, tab_label, position): (
+                LuaUserDataRef<gtk::Widget>,
+                Option<LuaUserDataRef<gtk::Widget>>,
+                Option<u32>,
+            )| { Ok(this.insert_page(&*child, tab_label.as_deref(), position)) },
+        );
+
+        reg.add_method("remove_page", |_, this, page_num: Option<u32>| {
+            this.remove_page(page_num);
+            Ok(())
+        });
+
+        reg.add_method("page_num", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            Ok(this.page_num(&*child))
+        });
+
+        reg.add_method("nth_page", |lua, this, page_num: Option<u32>| {
+            this.nth_page(page_num)
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
+
+        reg.add_method("n_pages", |_, this, ()| Ok(this.n_pages()));
+
+        reg.add_method("set_current_page", |_, this, page_num: Option<u32>| {
+            this.set_current_page(page_num);
+            Ok(())
+        });
+
+        reg.add_method("current_page", |_, this, ()| Ok(this.current_page()));
+
+        reg.add_method("next_page", |_, this, ()| {
+            this.next_page();
+            Ok(())
+        });
+
+        reg.add_method("prev_page", |_, this, ()| {
+            this.prev_page();
+            Ok(())
+        });
+
+        reg.add_method(
+            "set_tab_label",
+            |_,
+             this,
+             (child, tab_label): (
+                LuaUserDataRef<gtk::Widget>,
+                Option<LuaUserDataRef<gtk::Widget>>,
+            )| {
+                this.set_tab_label(&*child, tab_label.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_tab_label_text",
+            |_, this, (child, text): (LuaUserDataRef<gtk::Widget>, String)| {
+                this.set_tab_label_text(&*child, &text);
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_tab_reorderable",
+            |_, this, (child, reorderable): (LuaUserDataRef<gtk::Widget>, bool)| {
+                this.set_tab_reorderable(&*child, reorderable);
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_tab_pos", |_, this, pos: enums::PositionType| {
+            this.set_tab_pos(pos.0);
+            Ok(())
+        });
+
+        reg.add_method("set_show_tabs", |_, this, show_tabs: bool| {
+            this.set_show_tabs(show_tabs);
+            Ok(())
+        });
+
+        reg.add_method("set_show_border", |_, this, show_border: bool| {
+            this.set_show_border(show_border);
+            Ok(())
+        });
+
+        reg.add_method("set_scrollable", |_, this, scrollable: bool| {
+            this.set_scrollable(scrollable);
+            Ok(())
+        });
+
+        reg.add_method("connect_switch_page", |_, this, f: LuaOwnedFunction| {
+            this.connect_switch_page(move |_, _, page_num| {
+                catch_lua_errors::<_, ()>(f.to_ref(), page_num);
+            });
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| {
+                let notebook = Notebook::new();
+                lua.create_any_userdata(notebook)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for ScrolledWindow {
+    const CLASS_NAME: &'static str = "ScrolledWindow";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_child",
+            |_, this, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_child(child.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_policy",
+            |_, this, (hpolicy, vpolicy): (enums::PolicyType, enums::PolicyType)| {
+                this.set_policy(hpolicy.0, vpolicy.0);
+                Ok(())
+            },
+        );
+
+        reg.add_method("hscrollbar_policy", |_, this, ()| {
+            Ok(enums::PolicyType(this.hscrollbar_policy()))
+        });
+
+        reg.add_method("vscrollbar_policy", |_, this, ()| {
+            Ok(enums::PolicyType(this.vscrollbar_policy()))
+        });
+
+        reg.add_method("set_propagate_natural_width", |_, this, propagate: bool| {
+            this.set_propagate_natural_width(propagate);
+            Ok(())
+        });
+
+        reg.add_method(
+            "set_propagate_natural_height",
+            |_, this, propagate: bool| {
+                this.set_propagate_natural_height(propagate);
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_kinetic_scrolling", |_, this, kinetic: bool| {
+            this.set_kinetic_scrolling(kinetic);
+            Ok(())
+        });
+
+        reg.add_method("set_overlay_scrolling", |_, this, overlay: bool| {
+            this.set_overlay_scrolling(overlay);
+            Ok(())
+        });
+
+        reg.add_method("set_has_frame", |_, this, has_frame: bool| {
+            this.set_has_frame(has_frame);
+            Ok(())
+        });
+
+        reg.add_method("set_min_content_width", |_, this, width: i32| {
+            this.set_min_content_width(width);
+            Ok(())
+        });
+
+        reg.add_method("set_min_content_height", |_, this, height: i32| {
+            this.set_min_content_height(height);
+            Ok(())
+        });
+
+        reg.add_method("set_max_content_width", |_, this, width: i32| {
+            this.set_max_content_width(width);
+            Ok(())
+        });
+
+        reg.add_method("set_max_content_height", |_, this, height: i32| {
+            this.set_max_content_height(height);
+            Ok(())
+        });
+
+        reg.add_method("hscroll", |_, this, ()| Ok(this.hadjustment().value()));
+
+        reg.add_method("vscroll", |_, this, ()| Ok(this.vadjustment().value()));
+
+        reg.add_method("set_hscroll", |_, this, value: f64| {
+            this.hadjustment().set_value(value);
+            Ok(())
+        });
+
+        reg.add_method("set_vscroll", |_, this, value: f64| {
+            this.vadjustment().set_value(value);
+            Ok(())
+        });
+
+        reg.add_method("connect_edge_reached", |_, this, f: LuaOwnedFunction| {
+            this.connect_edge_reached(move |_, pos| {
+                catch_lua_errors::<_, ()>(f.to_ref(), enums::PositionType(pos));
+            });
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                let scrolled_window = ScrolledWindow::new();
+                scrolled_window.set_child(child.as_deref());
+                lua.create_any_userdata(scrolled_window)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Calls a Lua sort function, which returns a negative number if `a` goes before `b`,
+/// a positive number if it goes after it, or zero if their order doesn't matter
+fn call_sort_func<'lua>(
+    f: &'lua LuaOwnedFunction,
+    a: impl IntoLua<'lua>,
+    b: impl IntoLua<'lua>,
+) -> gtk::Ordering {
+    let result = catch_lua_errors::<_, f64>(f.to_ref(), (a, b)).unwrap_or(0.0);
+    result
+        .partial_cmp(&0.0)
+        .unwrap_or(std::cmp::Ordering::Equal)
+        .into()
+}
+
+impl LuaApi for ListBoxRow {
+    const CLASS_NAME: &'static str = "ListBoxRow";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_child",
+            |_, this, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_child(child.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method("child", |lua, this, ()| {
+            this.child()
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
+
+        reg.add_method("index", |_, this, ()| Ok(this.index()));
+
+        reg.add_method("is_selected", |_, this, ()| Ok(this.is_selected()));
+
+        reg.add_method("set_activatable", |_, this, activatable: bool| {
+            this.set_activatable(activatable);
+            Ok(())
+        });
+
+        reg.add_method("set_selectable", |_, this, selectable: bool| {
+            this.set_selectable(selectable);
+            Ok(())
+        });
+
+        // Makes the list box filter and sort the row again
+        reg.add_method("changed", |_, this, ()| {
+            this.changed();
+            Ok(())
+        });
+
+        register_signals!(reg, [activate]);
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                let row = ListBoxRow::new();
+                row.set_child(child.as_deref());
+                lua.create_any_userdata(row)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for ListBox {
+    const CLASS_NAME: &'static str = "ListBox";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [selected_rows_changed]);
+
+        reg.add_method("prepend", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.prepend(&*child);
+            Ok(())
+        });
+
+        reg.add_method("append", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.append(&*child);
+            Ok(())
+        });
+
+        reg.add_method(
+            "insert",
+            |_, this, (child, position): (LuaUserDataRef<gtk::Widget>, i32)| {
+                this.insert(&*child, position);
+                Ok(())
+            },
+        );
+
+        reg.add_method("remove", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.remove(&*child);
+            Ok(())
+        });
+
+        reg.add_method("remove_all", |_, this, ()| {
+            // Iterating over children would remove the placeholder too
+            while let Some(row) = this.row_at_index(0) {
+                this.remove(&row);
+            }
+
+            Ok(())
+        });
+
+        reg.add_method("row_at_index", |lua, this, index: i32| {
+            this.row_at_index(index)
+                .map(|row| lua.create_any_userdata(row))
+                .transpose()
+        });
+
+        reg.add_method(
+            "set_placeholder",
+            |_, this, placeholder: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_placeholder(placeholder.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_selection_mode",
+            |_, this, mode: enums::SelectionMode| {
+                this.set_selection_mode(mode.0);
+                Ok(())
+            },
+        );
+
+        reg.add_method("selection_mode", |_, this, ()| {
+            Ok(enums::SelectionMode(this.selection_mode()))
+        });
+
+        reg.add_method(
+            "select_row",
+            |_, this, row: Option<LuaUserDataRef<ListBoxRow>>| {
+                this.select_row(row.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "unselect_row",
+            |_, this, row: LuaUserDataRef<ListBoxRow>| {
+                this.unselect_row(&*row);
+                Ok(())
+            },
+        );
+
+        reg.add_method("select_all", |_, this, ()| {
+            this.select_all();
+            Ok(())
+        });
+
+        reg.add_method("unselect_all", |_, this, ()| {
+            this.unselect_all();
+            Ok(())
+        });
+
+        reg.add_method("selected_row", |lua, this, ()| {
+            this.selected_row()
+                .map(|row| lua.create_any_userdata(row))
+                .transpose()
+        });
+
+        reg.add_method("selected_rows", |lua, this, ()| {
+            lua.create_sequence_from(this.selected_rows().into_iter().map(UserDataWrapper))
+        });
+
+        reg.add_method("set_activate_on_single_click", |_, this, single: bool| {
+            this.set_activate_on_single_click(single);
+            Ok(())
+        });
+
+        reg.add_method("set_show_separators", |_, this, show_separators: bool| {
+            this.set_show_separators(show_separators);
+            Ok(())
+        });
+
+        reg.add_method("connect_row_activated", |_, this, f: LuaOwnedFunction| {
+            this.connect_row_activated(move |_, row| {
+                catch_lua_errors::<_, ()>(f.to_ref(), UserDataWrapper(row.clone()));
+            });
+            Ok(())
+        });
+
+        reg.add_method("connect_row_selected", |_, this, f: LuaOwnedFunction| {
+            this.connect_row_selected(move |_, row| {
+                catch_lua_errors::<_, ()>(f.to_ref(), row.cloned().map(UserDataWrapper));
+            });
+            Ok(())
+        });
+
+        reg.add_method("set_filter_func", |_, this, f: LuaOwnedFunction| {
+            this.set_filter_func(move |row| {
+                catch_lua_errors::<_, bool>(f.to_ref(), UserDataWrapper(row.clone()))
+                    .unwrap_or(true)
+            });
+            Ok(())
+        });
+
+        reg.add_method("unset_filter_func", |_, this, ()| {
+            this.unset_filter_func();
+            Ok(())
+        });
+
+        reg.add_method("invalidate_filter", |_, this, ()| {
+            this.invalidate_filter();
+            Ok(())
+        });
+
+        reg.add_method("set_sort_func", |_, this, f: LuaOwnedFunction| {
+            this.set_sort_func(move |a, b| {
+                call_sort_func(&f, UserDataWrapper(a.clone()), UserDataWrapper(b.clone()))
+            });
+            Ok(())
+        });
+
+        reg.add_method("unset_sort_func", |_, this, ()| {
+            this.unset_sort_func();
+            Ok(())
+        });
+
+        reg.add_method("invalidate_sort", |_, this, ()| {
+            this.invalidate_sort();
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| {
+                let list_box = ListBox::new();
+                lua.create_any_userdata(list_box)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for FlowBoxChild {
+    const CLASS_NAME: &'static str = "FlowBoxChild";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_child",
+            |_, this, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_child(child.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method("child", |lua, this, ()| {
+            this.child()
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
+
+        reg.add_method("index", |_, this, ()| Ok(this.index()));
+
+        reg.add_method("is_selected", |_, this, ()| Ok(this.is_selected()));
+
+        // Makes the flow box filter and sort the child again
+        reg.add_method("changed", |_, this, ()| {
+            this.changed();
+            Ok(())
+        });
+
+        register_signals!(reg, [activate]);
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                let flow_box_child = FlowBoxChild::new();
+                flow_box_child.set_child(child.as_deref());
+                lua.create_any_userdata(flow_box_child)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for FlowBox {
+    const CLASS_NAME: &'static str = "FlowBox";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [selected_children_changed]);
+
+        reg.add_method("prepend", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.prepend(&*child);
+            Ok(())
+        });
+
+        reg.add_method("append", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.append(&*child);
+            Ok(())
+        });
+
+        reg.add_method(
+            "insert",
+            |_, this, (child, position): (LuaUserDataRef<gtk::Widget>, i32)| {
+                this.insert(&*child, position);
+                Ok(())
+            },
+        );
+
+        reg.add_method("remove", |_, this, child: LuaUserDataRef<gtk::Widget>| {
+            this.remove(&*child);
+            Ok(())
+        });
+
+        reg.add_method("remove_all", |_, this, ()| {
+            while let Some(child) = this.first_child() {
+                this.remove(&child);
+            }
+
+            Ok(())
+        });
+
+        reg.add_method("child_at_index", |lua, this, index: i32| {
+            this.child_at_index(index)
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
+
+        reg.add_method(
+            "set_orientation",
+            |_, this, orientation: enums::Orientation| {
+                this.set_orientation(orientation.0);
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_homogeneous", |_, this, homogeneous: bool| {
+            this.set_homogeneous(homogeneous);
+            Ok(())
+        });
+
+        reg.add_method("set_min_children_per_line", |_, this, n_children: u32| {
+            this.set_min_children_per_line(n_children);
+            Ok(())
+        });
+
+        reg.add_method("set_max_children_per_line", |_, this, n_children: u32| {
+            this.set_max_children_per_line(n_children);
+            Ok(())
+        });
+
+        reg.add_method("set_row_spacing", |_, this, spacing: u32| {
+            this.set_row_spacing(spacing);
+            Ok(())
+        });
+
+        reg.add_method("set_column_spacing", |_, this, spacing: u32| {
+            this.set_column_spacing(spacing);
+            Ok(())
+        });
+
+        reg.add_method(
+            "set_selection_mode",
+            |_, this, mode: enums::SelectionMode| {
+                this.set_selection_mode(mode.0);
+                Ok(())
+            },
+        );
+
+        reg.add_method("selection_mode", |_, this, ()| {
+            Ok(enums::SelectionMode(this.selection_mode()))
+        });
+
+        reg.add_method(
+            "select_child",
+            |_, this, child: LuaUserDataRef<FlowBoxChild>| {
+                this.select_child(&*child);
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "unselect_child",
+            |_, this, child: LuaUserDataRef<FlowBoxChild>| {
+                this.unselect_child(&*child);
+                Ok(())
+            },
+        );
+
+        reg.add_method("select_all", |_, this, ()| {
+            this.select_all();
+            Ok(())
+        });
+
+        reg.add_method("unselect_all", |_, this, ()| {
+            this.unselect_all();
+            Ok(())
+        });
+
+        reg.add_method("selected_children", |lua, this, ()| {
+            lua.create_sequence_from(this.selected_children().into_iter().map(UserDataWrapper))
+        });
+
+        reg.add_method("set_activate_on_single_click", |_, this, single: bool| {
+            this.set_activate_on_single_click(single);
+            Ok(())
+        });
+
+        reg.add_method("connect_child_activated", |_, this, f: LuaOwnedFunction| {
+            this.connect_child_activated(move |_, child| {
+                catch_lua_errors::<_, ()>(f.to_ref(), UserDataWrapper(child.clone()));
+            });
+            Ok(())
+        });
+
+        reg.add_method("set_filter_func", |_, this, f: LuaOwnedFunction| {
+            this.set_filter_func(move |child| {
+                catch_lua_errors::<_, bool>(f.to_ref(), UserDataWrapper(child.clone()))
+                    .unwrap_or(true)
+            });
+            Ok(())
+        });
+
+        reg.add_method("unset_filter_func", |_, this, ()| {
+            this.unset_filter_func();
+            Ok(())
+        });
+
+        reg.add_method("invalidate_filter", |_, this, ()| {
+            this.invalidate_filter();
+            Ok(())
+        });
+
+        reg.add_method("set_sort_func", |_, this, f: LuaOwnedFunction| {
+            this.set_sort_func(move |a, b| {
+                call_sort_func(&f, UserDataWrapper(a.clone()), UserDataWrapper(b.clone()))
+            });
+            Ok(())
+        });
+
+        reg.add_method("unset_sort_func", |_, this, ()| {
+            this.unset_sort_func();
+            Ok(())
+        });
+
+        reg.add_method("invalidate_sort", |_, this, ()| {
+            this.invalidate_sort();
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| {
+                let flow_box = FlowBox::new();
+                lua.create_any_userdata(flow_box)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+pub(super) fn add_model_methods<T: glib::IsA<gio::ListModel> + glib::IsA<glib::Object>>(
+    reg: &mut LuaUserDataRegistry<'_, T>,
+) {
+    add_object_methods(reg);
+
+    reg.add_method("n_items", |_, this, ()| Ok(this.n_items()));
+
+    reg.add_method("get", |_, this, position: u32| {
+        Ok(ModelItemWrapper(this.item(position)))
+    });
+
+    reg.add_method("connect_items_changed", |_, this, f: LuaOwnedFunction| {
+        this.connect_items_changed(move |_, position, removed, added| {
+            catch_lua_errors::<_, ()>(f.to_ref(), (position, removed, added));
+        });
+        Ok(())
+    });
+}
+
+fn lua_filter(f: LuaOwnedFunction) -> CustomFilter {
+    CustomFilter::new(move |item| {
+        catch_lua_errors::<_, bool>(f.to_ref(), ModelItemWrapper(Some(item.clone())))
+            .unwrap_or(true)
+    })
+}
+
+fn lua_sorter(f: LuaOwnedFunction) -> CustomSorter {
+    CustomSorter::new(move |a, b| {
+        call_sort_func(
+            &f,
+            ModelItemWrapper(Some(a.clone())),
+            ModelItemWrapper(Some(b.clone())),
+        )
+    })
+}
+
+impl LuaApi for FilterListModel {
+    const CLASS_NAME: &'static str = "FilterListModel";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("upcast", |lua, this, ()| {
+            lua.create_any_userdata(this.clone().upcast::<gio::ListModel>())
+        });
+
+        reg.add_method("set_filter_func", |_, this, f: LuaOwnedFunction| {
+            this.set_filter(Some(&lua_filter(f)));
+            Ok(())
+        });
+
+        reg.add_method("unset_filter_func", |_, this, ()| {
+            this.set_filter(None::<&CustomFilter>);
+            Ok(())
+        });
+
+        reg.add_method("invalidate_filter", |_, this, ()| {
+            if let Some(filter) = this.filter() {
+                filter.changed(FilterChange::Different);
+            }
+            Ok(())
+        });
+
+        reg.add_method("set_incremental", |_, this, incremental: bool| {
+            this.set_incremental(incremental);
+            Ok(())
+        });
+
+        add_model_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(
+                |lua, (model, f): (LuaUserDataRef<gio::ListModel>, Option<LuaOwnedFunction>)| {
+                    let filter_model = FilterListModel::new(Some(model.clone()), f.map(lua_filter));
+                    lua.create_any_userdata(filter_model)
+                },
+            )?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for SortListModel {
+    const CLASS_NAME: &'static str = "SortListModel";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("upcast", |lua, this, ()| {
+            lua.create_any_userdata(this.clone().upcast::<gio::ListModel>())
+        });
+
+        reg.add_method("set_sort_func", |_, this, f: LuaOwnedFunction| {
+            this.set_sorter(Some(&lua_sorter(f)));
+            Ok(())
+        });
+
+        reg.add_method("unset_sort_func", |_, this, ()| {
+            this.set_sorter(None::<&CustomSorter>);
+            Ok(())
+        });
+
+        reg.add_method("invalidate_sort", |_, this, ()| {
+            if let Some(sorter) = this.sorter() {
+                sorter.changed(SorterChange::Different);
+            }
+            Ok(())
+        });
+
+        reg.add_method("set_incremental", |_, this, incremental: bool| {
+            this.set_incremental(incremental);
+            Ok(())
+        });
+
+        add_model_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(
+                |lua, (model, f): (LuaUserDataRef<gio::ListModel>, Option<LuaOwnedFunction>)| {
+                    let sort_model = SortListModel::new(Some(model.clone()), f.map(lua_sorter));
+                    lua.create_any_userdata(sort_model)
+                },
+            )?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for NoSelection {
+    const CLASS_NAME: &'static str = "NoSelection";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("upcast", |lua, this, ()| {
+            lua.create_any_userdata(this.clone().upcast::<gtk::SelectionModel>())
+        });
+
+        add_model_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, model: LuaUserDataRef<gio::ListModel>| {
+                let selection = NoSelection::new(Some(model.clone()));
+                lua.create_any_userdata(selection)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for SingleSelection {
+    const CLASS_NAME: &'static str = "SingleSelection";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [selected_notify]);
+
+        reg.add_method("upcast", |lua, this, ()| {
+            lua.create_any_userdata(this.clone().upcast::<gtk::SelectionModel>())
+        });
+
+        reg.add_method("selected", |_, this, ()| {
+            let selected = this.selected();
+            Ok((selected != gtk::INVALID_LIST_POSITION).then_some(selected))
+        });
+
+        reg.add_method("set_selected", |_, this, position: Option<u32>| {
+            this.set_selected(position.unwrap_or(gtk::INVALID_LIST_POSITION));
+            Ok(())
+        });
+
+        reg.add_method("selected_item", |_, this, ()| {
+            Ok(ModelItemWrapper(this.selected_item()))
+        });
+
+        reg.add_method("set_autoselect", |_, this, autoselect: bool| {
+            this.set_autoselect(autoselect);
+            Ok(())
+        });
+
+        reg.add_method("set_can_unselect", |_, this, can_unselect: bool| {
+            this.set_can_unselect(can_unselect);
+            Ok(())
+        });
+
+        add_model_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, model: LuaUserDataRef<gio::ListModel>| {
+                let selection = SingleSelection::new(Some(model.clone()));
+                lua.create_any_userdata(selection)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for MultiSelection {
+    const CLASS_NAME: &'static str = "MultiSelection";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("upcast", |lua, this, ()| {
+            lua.create_any_userdata(this.clone().upcast::<gtk::SelectionModel>())
+        });
+
+        reg.add_method("is_selected", |_, this, position: u32| {
+            Ok(this.is_selected(position))
+        });
+
+        reg.add_method(
+            "select_item",
+            |_, this, (position, unselect_rest): (u32, Option<bool>)| {
+                Ok(this.select_item(position, unselect_rest.unwrap_or(false)))
+            },
+        );
+
+        reg.add_method("unselect_item", |_, this, position: u32| {
+            Ok(this.unselect_item(position))
+        });
+
+        reg.add_method("select_all", |_, this, ()| Ok(this.select_all()));
+
+        reg.add_method("unselect_all", |_, this, ()| Ok(this.unselect_all()));
+
+        reg.add_method(
+            "connect_selection_changed",
+            |_, this, f: LuaOwnedFunction| {
+                this.connect_selection_changed(move |_, position, n_items| {
+                    catch_lua_errors::<_, ()>(f.to_ref(), (position, n_items));
+                });
+                Ok(())
+            },
+        );
+
+        add_model_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, model: LuaUserDataRef<gio::ListModel>| {
+                let selection = MultiSelection::new(Some(model.clone()));
+                lua.create_any_userdata(selection)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for ListItem {
+    const CLASS_NAME: &'static str = "ListItem";
+    const CONSTRUCTIBLE: bool = false;
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_child",
+            |_, this, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_child(child.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method("child", |lua, this, ()| {
+            this.child()
+                .map(|child| lua.create_any_userdata(child))
+                .transpose()
+        });
+
+        reg.add_method("item", |_, this, ()| Ok(ModelItemWrapper(this.item())));
+
+        reg.add_method("position", |_, this, ()| {
+            let position = this.position();
+            Ok((position != gtk::INVALID_LIST_POSITION).then_some(position))
+        });
+
+        reg.add_method("is_selected", |_, this, ()| Ok(this.is_selected()));
+
+        reg.add_method("set_activatable", |_, this, activatable: bool| {
+            this.set_activatable(activatable);
+            Ok(())
+        });
+
+        reg.add_method("set_selectable", |_, this, selectable: bool| {
+            this.set_selectable(selectable);
+            Ok(())
+        });
+
+        add_object_methods(reg);
+    }
+}
+
+/// Connects a Lua callback to a `SignalListItemFactory` signal.
+/// The callback gets the list item and its Lua value if `with_item` is true
+fn connect_factory_signal(
+    f: LuaOwnedFunction,
+    with_item: bool,
+) -> impl Fn(&SignalListItemFactory, &glib::Object) + 'static {
+    move |_, object| {
+        let Some(list_item) = object.downcast_ref::<ListItem>() else {
+            return;
+        };
+
+        if with_item {
+            let item = ModelItemWrapper(list_item.item());
+            catch_lua_errors::<_, ()>(f.to_ref(), (UserDataWrapper(list_item.clone()), item));
+        } else {
+            catch_lua_errors::<_, ()>(f.to_ref(), UserDataWrapper(list_item.clone()));
+        }
+    }
+}
+
+impl LuaApi for SignalListItemFactory {
+    const CLASS_NAME: &'static str = "SignalListItemFactory";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("connect_setup", |_, this, f: LuaOwnedFunction| {
+            this.connect_setup(connect_factory_signal(f, false));
+            Ok(())
+        });
+
+        reg.add_method("connect_bind", |_, this, f: LuaOwnedFunction| {
+            this.connect_bind(connect_factory_signal(f, true));
+            Ok(())
+        });
+
+        reg.add_method("connect_unbind", |_, this, f: LuaOwnedFunction| {
+            this.connect_unbind(connect_factory_signal(f, true));
+            Ok(())
+        });
+
+        reg.add_method("connect_teardown", |_, this, f: LuaOwnedFunction| {
+            this.connect_teardown(connect_factory_signal(f, false));
+            Ok(())
+        });
+
+        add_object_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(
+                |lua, (setup, bind): (Option<LuaOwnedFunction>, Option<LuaOwnedFunction>)| {
+                    let factory = SignalListItemFactory::new();
+                    if let Some(setup) = setup {
+                        factory.connect_setup(connect_factory_signal(setup, false));
+                    }
+                    if let Some(bind) = bind {
+                        factory.connect_bind(connect_factory_signal(bind, true));
+                    }
+                    lua.create_any_userdata(factory)
+                },
+            )?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for ListView {
+    const CLASS_NAME: &'static str = "ListView";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_model",
+            |_, this, model: Option<LuaUserDataRef<gtk::SelectionModel>>| {
+                this.set_model(model.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_factory",
+            |_, this, factory: Option<LuaUserDataRef<SignalListItemFactory>>| {
+                this.set_factory(factory.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_orientation",
+            |_, this, orientation: enums::Orientation| {
+                this.set_orientation(orientation.0);
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_show_separators", |_, this, show_separators: bool| {
+            this.set_show_separators(show_separators);
+            Ok(())
+        });
+
+        reg.add_method("set_single_click_activate", |_, this, single: bool| {
+            this.set_single_click_activate(single);
+            Ok(())
+        });
+
+        reg.add_method("set_enable_rubberband", |_, this, enable: bool| {
+            this.set_enable_rubberband(enable);
+            Ok(())
+        });
+
+        reg.add_method("connect_activate", |_, this, f: LuaOwnedFunction| {
+            this.connect_activate(move |_, position| {
+                catch_lua_errors::<_, ()>(f.to_ref(), position);
+            });
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(
+                |lua,
+                 (model, factory): (
+                    Option<LuaUserDataRef<gtk::SelectionModel>>,
+                    Option<LuaUserDataRef<SignalListItemFactory>>,
+                )| {
+                    let list_view = ListView::new(
+                        model.map(|model| (*model).clone()),
+                        factory.map(|factory| (*factory).clone()),
+                    );
+                    lua.create_any_userdata(list_view)
+                },
+            )?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for GridView {
+    const CLASS_NAME: &'static str = "GridView";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_model",
+            |_, this, model: Option<LuaUserDataRef<gtk::SelectionModel>>| {
+                this.set_model(model.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_factory",
+            |_, this, factory: Option<LuaUserDataRef<SignalListItemFactory>>| {
+                this.set_factory(factory.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "set_orientation",
+            |_, this, orientation: enums::Orientation| {
+                this.set_orientation(orientation.0);
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_min_columns", |_, this, min_columns: u32| {
+            this.set_min_columns(min_columns);
+            Ok(())
+        });
+
+        reg.add_method("set_max_columns", |_, this, max_columns: u32| {
+            this.set_max_columns(max_columns);
+            Ok(())
+        });
+
+        reg.add_method("set_single_click_activate", |_, this, single: bool| {
+            this.set_single_click_activate(single);
+            Ok(())
+        });
+
+        reg.add_method("set_enable_rubberband", |_, this, enable: bool| {
+            this.set_enable_rubberband(enable);
+            Ok(())
+        });
+
+        reg.add_method("connect_activate", |_, this, f: LuaOwnedFunction| {
+            this.connect_activate(move |_, position| {
+                catch_lua_errors::<_, ()>(f.to_ref(), position);
+            });
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(
+                |lua,
+                 (model, factory): (
+                    Option<LuaUserDataRef<gtk::SelectionModel>>,
+                    Option<LuaUserDataRef<SignalListItemFactory>>,
+                )| {
+                    let grid_view = GridView::new(
+                        model.map(|model| (*model).clone()),
+                        factory.map(|factory| (*factory).clone()),
+                    );
+                    lua.create_any_userdata(grid_view)
+                },
+            )?,
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Number of open popovers of a layer shell window whose keyboard mode has been changed
+/// by `allow_popover_focus`
+const OPEN_POPOVERS_KEY: &str = "crabshell-open-popovers";
+/// Marks popovers that `allow_popover_focus` has been applied to
+const POPOVER_FOCUS_KEY: &str = "crabshell-popover-focus";
+
+/// Autohide popovers grab the keyboard, which fails on layer shell windows that don't accept
+/// keyboard focus, making the popover close right after opening.
+/// Lets the window take focus on demand while any of its popovers is open
+fn allow_popover_focus(popover: &impl glib::IsA<Popover>) {
+    let popover = popover.upcast_ref::<Popover>();
+    // SAFETY: the keys are only ever used with these types
+    unsafe {
+        if popover.data::<bool>(POPOVER_FOCUS_KEY).is_some() {
+            return;
+        }
+        popover.set_data(POPOVER_FOCUS_KEY, true);
+    }
+
+    let focused_window = Rc::new(RefCell::new(None::<gtk::Window>));
+
+    let shown_window = focused_window.clone();
+    popover.connect_show(move |popover| {
+        if shown_window.borrow().is_some() {
+            return;
+        }
+
+        let window = popover
+            .parent()
+            .and_then(|parent| parent.root())
+            .and_then(|root| root.downcast::<gtk::Window>().ok())
+            .filter(|window| window.is_layer_window());
+        let Some(window) = window else {
+            return;
+        };
+
+        unsafe {
+            if let Some(open_popovers) = window.data::<Cell<usize>>(OPEN_POPOVERS_KEY) {
+                let open_popovers = open_popovers.as_ref();
+                open_popovers.set(open_popovers.get() + 1);
+            } else if window.keyboard_mode() == KeyboardMode::None {
+                window.set_keyboard_mode(KeyboardMode::OnDemand);
+                window.set_data(OPEN_POPOVERS_KEY, Cell::new(1usize));
+            } else {
+                return;
+            }
+        }
+
+        shown_window.replace(Some(window));
+    });
+
+    popover.connect_closed(move |_| {
+        let Some(window) = focused_window.take() else {
+            return;
+        };
+
+        unsafe {
+            let Some(open_popovers) = window.data::<Cell<usize>>(OPEN_POPOVERS_KEY) else {
+                return;
+            };
+
+            let remaining = open_popovers.as_ref().get().saturating_sub(1);
+            open_popovers.as_ref().set(remaining);
+            if remaining == 0 {
+                window.steal_data::<Cell<usize>>(OPEN_POPOVERS_KEY);
+                window.set_keyboard_mode(KeyboardMode::None);
+            }
+        }
+    });
+}
+
+/// Menu buttons create popovers for menu models themselves, and popovers can be set
+/// with `set_property` too, so focus handling is applied whenever the popover changes
+fn allow_menu_button_focus(menu_button: &MenuButton) {
+    menu_button.connect_popover_notify(|menu_button| {
+        if let Some(popover) = menu_button.popover() {
+            allow_popover_focus(&popover);
+        }
+    });
+}
+
+fn add_popover_methods<T: glib::IsA<Popover> + glib::IsA<gtk::Widget>>(
+    reg: &mut LuaUserDataRegistry<'_, T>,
+) {
+    register_signals!(reg, [closed]);
+
+    reg.add_method(
+        "set_parent",
+        |_, this, parent: LuaUserDataRef<gtk::Widget>| {
+            this.set_parent(&*parent);
+            Ok(())
+        },
+    );
+
+    reg.add_method("unparent", |_, this, ()| {
+        this.unparent();
+        Ok(())
+    });
+
+    reg.add_method("popup", |_, this, ()| {
+        this.popup();
+        Ok(())
+    });
+
+    reg.add_method("popdown", |_, this, ()| {
+        this.popdown();
+        Ok(())
+    });
+
+    reg.add_method(
+        "set_child",
+        |_, this, child: Option<LuaUserDataRef<gtk::Widget>>| {
+            this.set_child(child.as_deref());
+            Ok(())
+        },
+    );
+
+    reg.add_method("set_position", |_, this, position: enums::PositionType| {
+        this.set_position(position.0);
+        Ok(())
+    });
+
+    reg.add_method("set_autohide", |_, this, autohide: bool| {
+        this.set_autohide(autohide);
+        Ok(())
+    });
+
+    reg.add_method("set_has_arrow", |_, this, has_arrow: bool| {
+        this.set_has_arrow(has_arrow);
+        Ok(())
+    });
+
+    reg.add_method("set_offset", |_, this, (x, y): (i32, i32)| {
+        this.set_offset(x, y);
+        Ok(())
+    });
+
+    reg.add_method(
+        "set_pointing_to",
+        |_, this, (x, y, width, height): (Option<i32>, Option<i32>, Option<i32>, Option<i32>)| {
+            let rect = x
+                .zip(y)
+                .map(|(x, y)| gdk::Rectangle::new(x, y, width.unwrap_or(1), height.unwrap_or(1)));
+            this.set_pointing_to(rect.as_ref());
+            Ok(())
+        },
+    );
+
+    reg.add_method("set_cascade_popdown", |_, this, cascade_popdown: bool| {
+        this.set_cascade_popdown(cascade_popdown);
+        Ok(())
+    });
+}
+
+impl LuaApi for Popover {
+    const CLASS_NAME: &'static str = "Popover";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        add_popover_methods(reg);
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                let popover = Popover::new();
+                popover.set_child(child.as_deref());
+                allow_popover_focus(&popover);
+                lua.create_any_userdata(popover)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for PopoverMenu {
+    const CLASS_NAME: &'static str = "PopoverMenu";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_menu_model",
+            |_, this, menu: Option<LuaUserDataRef<gio::Menu>>| {
+                this.set_menu_model(menu.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method(
+            "add_child",
+            |_, this, (child, id): (LuaUserDataRef<gtk::Widget>, String)| {
+                Ok(this.add_child(&*child, &id))
+            },
+        );
+
+        reg.add_method(
+            "remove_child",
+            |_, this, child: LuaUserDataRef<gtk::Widget>| Ok(this.remove_child(&*child)),
+        );
+
+        add_popover_methods(reg);
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, menu: Option<LuaUserDataRef<gio::Menu>>| {
+                let popover = PopoverMenu::from_model(menu.as_deref());
+                allow_popover_focus(&popover);
+                lua.create_any_userdata(popover)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for MenuButton {
+    const CLASS_NAME: &'static str = "MenuButton";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method(
+            "set_popover",
+            |_, this, popover: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_popover(popover.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method("popover", |lua, this, ()| {
+            this.popover()
+                .map(|popover| lua.create_any_userdata(popover))
+                .transpose()
+        });
+
+        reg.add_method(
+            "set_menu_model",
+            |_, this, menu: Option<LuaUserDataRef<gio::Menu>>| {
+                this.set_menu_model(menu.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_label", |_, this, label: String| {
+            this.set_label(&label);
+            Ok(())
+        });
+
+        reg.add_method("set_icon_name", |_, this, icon_name: String| {
+            this.set_icon_name(&icon_name);
+            Ok(())
+        });
+
+        reg.add_method(
+            "set_child",
+            |_, this, child: Option<LuaUserDataRef<gtk::Widget>>| {
+                this.set_child(child.as_deref());
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_direction", |_, this, direction: enums::ArrowType| {
+            this.set_direction(direction.0);
+            Ok(())
+        });
+
+        reg.add_method("set_has_frame", |_, this, has_frame: bool| {
+            this.set_has_frame(has_frame);
+            Ok(())
+        });
+
+        reg.add_method(
+            "set_always_show_arrow",
+            |_, this, always_show_arrow: bool| {
+                this.set_always_show_arrow(always_show_arrow);
+                Ok(())
+            },
+        );
+
+        reg.add_method("set_primary", |_, this, primary: bool| {
+            this.set_primary(primary);
+            Ok(())
+        });
+
+        reg.add_method("set_active", |_, this, active: bool| {
+            this.set_active(active);
+            Ok(())
+        });
+
+        reg.add_method("is_active", |_, this, ()| Ok(this.is_active()));
+
+        reg.add_method("popup", |_, this, ()| {
+            this.popup();
+            Ok(())
+        });
+
+        reg.add_method("popdown", |_, this, ()| {
+            this.popdown();
+            Ok(())
+        });
+
+        add_widget_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, menu: Option<LuaUserDataRef<gio::Menu>>| {
+                let menu_button = MenuButton::new();
+                allow_menu_button_focus(&menu_button);
+                menu_button.set_menu_model(menu.as_deref());
+                lua.create_any_userdata(menu_button)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for EventControllerKey {
+    const CLASS_NAME: &'static str = "EventControllerKey";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        add_controller_methods(reg);
+
+        reg.add_method("forward", |_, this, widget: LuaUserDataRef<gtk::Widget>| {
+            Ok(this.forward(&*widget))
+        });
+
+        reg.add_method("connect_key_pressed", |_, this, f: LuaOwnedFunction| {
+            this.connect_key_pressed(move |_, key, keycode, state| {
+                let key_name = key.name().map(GStringWrapper);
+                let state = ModifierTypeWrapper(state);
+                let result = f
+                    .call::<_, Option<bool>>((key_name, keycode, state))
+                    .unwrap();
 
-        reg.add_method("upcast", |lua, this, ()| {
-            lua.create_any_userdata(this.clone().upcast::<gtk::EventController>())
+                if result.unwrap_or(false) {
+                    glib::Propagation::Stop
+                } else {
+                    glib::Propagation::Proceed
+                }
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("connect_key_released", |_, this, f: LuaOwnedFunction| {
+            this.connect_key_pressed(move |_, key, keycode, state| {
+                let key_name = key.name().map(GStringWrapper);
+                let state = ModifierTypeWrapper(state);
+                let result = f
+                    .call::<_, Option<bool>>((key_name, keycode, state))
+                    .unwrap();
+
+                if result.unwrap_or(false) {
+                    glib::Propagation::Stop
+                } else {
+                    glib::Propagation::Proceed
+                }
+            });
+
+            Ok(())
+        });
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| {
+                let event_controller = EventControllerKey::new();
+                lua.create_any_userdata(event_controller)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for EventControllerScroll {
+    const CLASS_NAME: &'static str = "EventControllerScroll";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [scroll_begin, scroll_end]);
+
+        add_controller_methods(reg);
+
+        reg.add_method("connect_scroll", |_, this, f: LuaOwnedFunction| {
+            this.connect_scroll(move |_, dx, dy| {
+                let result =
+                    catch_lua_errors::<_, Option<bool>>(f.to_ref(), (dx, dy)).unwrap_or(None);
+                if result.unwrap_or(false) {
+                    glib::Propagation::Stop
+                } else {
+                    glib::Propagation::Proceed
+                }
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("connect_decelerate", |_, this, f: LuaOwnedFunction| {
+            this.connect_decelerate(move |_, vel_x, vel_y| {
+                catch_lua_errors::<_, ()>(f.to_ref(), (vel_x, vel_y));
+            });
+
+            Ok(())
+        });
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, flags: EventControllerScrollFlagsWrapper| {
+                let event_controller = EventControllerScroll::new(flags.0);
+                lua.create_any_userdata(event_controller)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for EventControllerMotion {
+    const CLASS_NAME: &'static str = "EventControllerMotion";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [leave]);
+
+        add_controller_methods(reg);
+
+        reg.add_method("connect_enter", |_, this, f: LuaOwnedFunction| {
+            this.connect_enter(move |_, x, y| {
+                catch_lua_errors::<_, ()>(f.to_ref(), (x, y));
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("connect_motion", |_, this, f: LuaOwnedFunction| {
+            this.connect_motion(move |_, x, y| {
+                catch_lua_errors::<_, ()>(f.to_ref(), (x, y));
+            });
+
+            Ok(())
         });
     }
 
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, ()| {
+                let event_controller = EventControllerMotion::new();
+                lua.create_any_userdata(event_controller)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for EventControllerFocus {
+    const CLASS_NAME: &'static str = "EventControllerFocus";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [enter, leave]);
+
+        add_controller_methods(reg);
+    }
+
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
         table.set(
             "new",
@@ -1453,6 +3532,218 @@ impl LuaApi for EventControllerFocus {
     }
 }
 
+fn add_controller_methods<T: glib::IsA<gtk::EventController> + glib::IsA<glib::Object>>(
+    reg: &mut LuaUserDataRegistry<'_, T>,
+) {
+    reg.add_method("upcast", |lua, this, ()| {
+        lua.create_any_userdata(this.clone().upcast::<gtk::EventController>())
+    });
+
+    add_object_methods(reg);
+}
+
+fn add_gesture_methods<
+    T: glib::IsA<gtk::GestureSingle>
+        + glib::IsA<gtk::Gesture>
+        + glib::IsA<gtk::EventController>
+        + glib::IsA<glib::Object>,
+>(
+    reg: &mut LuaUserDataRegistry<'_, T>,
+) {
+    add_controller_methods(reg);
+
+    reg.add_method("set_button", |_, this, button: u32| {
+        this.set_button(button);
+        Ok(())
+    });
+
+    reg.add_method("button", |_, this, ()| Ok(this.button()));
+
+    reg.add_method("current_button", |_, this, ()| Ok(this.current_button()));
+
+    reg.add_method("set_touch_only", |_, this, touch_only: bool| {
+        this.set_touch_only(touch_only);
+        Ok(())
+    });
+
+    reg.add_method("set_exclusive", |_, this, exclusive: bool| {
+        this.set_exclusive(exclusive);
+        Ok(())
+    });
+
+    reg.add_method("claim", |_, this, ()| {
+        Ok(this.set_state(gtk::EventSequenceState::Claimed))
+    });
+}
+
+impl LuaApi for GestureClick {
+    const CLASS_NAME: &'static str = "GestureClick";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [stopped]);
+
+        reg.add_method("connect_pressed", |_, this, f: LuaOwnedFunction| {
+            this.connect_pressed(move |gesture, n_press, x, y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (n_press, x, y, button));
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("connect_released", |_, this, f: LuaOwnedFunction| {
+            this.connect_released(move |gesture, n_press, x, y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (n_press, x, y, button));
+            });
+
+            Ok(())
+        });
+
+        add_gesture_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, button: Option<u32>| {
+                let gesture = GestureClick::new();
+                if let Some(button) = button {
+                    gesture.set_button(button);
+                }
+                lua.create_any_userdata(gesture)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for GestureLongPress {
+    const CLASS_NAME: &'static str = "GestureLongPress";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        register_signals!(reg, [cancelled]);
+
+        reg.add_method("connect_pressed", |_, this, f: LuaOwnedFunction| {
+            this.connect_pressed(move |gesture, x, y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (x, y, button));
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("set_delay_factor", |_, this, delay_factor: f64| {
+            this.set_delay_factor(delay_factor);
+            Ok(())
+        });
+
+        add_gesture_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, button: Option<u32>| {
+                let gesture = GestureLongPress::new();
+                if let Some(button) = button {
+                    gesture.set_button(button);
+                }
+                lua.create_any_userdata(gesture)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for GestureSwipe {
+    const CLASS_NAME: &'static str = "GestureSwipe";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("connect_swipe", |_, this, f: LuaOwnedFunction| {
+            this.connect_swipe(move |gesture, velocity_x, velocity_y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (velocity_x, velocity_y, button));
+            });
+
+            Ok(())
+        });
+
+        add_gesture_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, button: Option<u32>| {
+                let gesture = GestureSwipe::new();
+                if let Some(button) = button {
+                    gesture.set_button(button);
+                }
+                lua.create_any_userdata(gesture)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for GestureDrag {
+    const CLASS_NAME: &'static str = "GestureDrag";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("connect_drag_begin", |_, this, f: LuaOwnedFunction| {
+            this.connect_drag_begin(move |gesture, start_x, start_y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (start_x, start_y, button));
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("connect_drag_update", |_, this, f: LuaOwnedFunction| {
+            this.connect_drag_update(move |gesture, offset_x, offset_y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (offset_x, offset_y, button));
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("connect_drag_end", |_, this, f: LuaOwnedFunction| {
+            this.connect_drag_end(move |gesture, offset_x, offset_y| {
+                let button = gesture.current_button();
+                catch_lua_errors::<_, ()>(f.to_ref(), (offset_x, offset_y, button));
+            });
+
+            Ok(())
+        });
+
+        reg.add_method("start_point", |_, this, ()| Ok(this.start_point().unzip()));
+
+        reg.add_method("offset", |_, this, ()| Ok(this.offset().unzip()));
+
+        add_gesture_methods(reg);
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, button: Option<u32>| {
+                let gesture = GestureDrag::new();
+                if let Some(button) = button {
+                    gesture.set_button(button);
+                }
+                lua.create_any_userdata(gesture)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
 impl LuaApi for IconPaintable {
     const CLASS_NAME: &'static str = "IconPaintable";
 
@@ -1482,6 +3773,8 @@ impl LuaApi for IconPaintable {
         });
 
         reg.add_method("is_symbolic", |_, this, ()| Ok(this.is_symbolic()));
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -1551,6 +3844,8 @@ impl LuaApi for IconTheme {
                 lua.create_any_userdata(icon_paintable)
             },
         );
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -1618,6 +3913,8 @@ impl LuaApi for Settings {
                 Ok(())
             },
         );
+
+        add_object_methods(reg);
     }
 }
 
@@ -1634,6 +3931,8 @@ impl LuaApi for CssProvider {
             this.load_from_path(path);
             Ok(())
         });
+
+        add_object_methods(reg);
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -1777,10 +4076,35 @@ pub fn push_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
     Image::push_lua(lua, &gtk_table)?;
     Scale::push_lua(lua, &gtk_table)?;
     Revealer::push_lua(lua, &gtk_table)?;
+    Stack::push_lua(lua, &gtk_table)?;
+    StackSwitcher::push_lua(lua, &gtk_table)?;
+    StackSidebar::push_lua(lua, &gtk_table)?;
+    Notebook::push_lua(lua, &gtk_table)?;
+    ScrolledWindow::push_lua(lua, &gtk_table)?;
+    ListBox::push_lua(lua, &gtk_table)?;
+    ListBoxRow::push_lua(lua, &gtk_table)?;
+    FlowBox::push_lua(lua, &gtk_table)?;
+    FlowBoxChild::push_lua(lua, &gtk_table)?;
+    FilterListModel::push_lua(lua, &gtk_table)?;
+    SortListModel::push_lua(lua, &gtk_table)?;
+    NoSelection::push_lua(lua, &gtk_table)?;
+    SingleSelection::push_lua(lua, &gtk_table)?;
+    MultiSelection::push_lua(lua, &gtk_table)?;
+    ListItem::push_lua(lua, &gtk_table)?;
+    SignalListItemFactory::push_lua(lua, &gtk_table)?;
+    ListView::push_lua(lua, &gtk_table)?;
+    GridView::push_lua(lua, &gtk_table)?;
+    Popover::push_lua(lua, &gtk_table)?;
+    PopoverMenu::push_lua(lua, &gtk_table)?;
+    MenuButton::push_lua(lua, &gtk_table)?;
     EventControllerKey::push_lua(lua, &gtk_table)?;
     EventControllerScroll::push_lua(lua, &gtk_table)?;
     EventControllerMotion::push_lua(lua, &gtk_table)?;
     EventControllerFocus::push_lua(lua, &gtk_table)?;
+    GestureClick::push_lua(lua, &gtk_table)?;
+    GestureLongPress::push_lua(lua, &gtk_table)?;
+    GestureSwipe::push_lua(lua, &gtk_table)?;
+    GestureDrag::push_lua(lua, &gtk_table)?;
     IconPaintable::push_lua(lua, &gtk_table)?;
     IconTheme::push_lua(lua, &gtk_table)?;
     Settings::push_lua(lua, &gtk_table)?;
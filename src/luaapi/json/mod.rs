@@ -5,6 +5,48 @@ mod conversions;
 mod error;
 
 use self::conversions::{TryFromJson, TryJsonFrom};
+use crate::traits::LuaApi;
+
+/// Parsed JSON, kept on the Rust side until converted with `to_lua`
+#[derive(Clone)]
+pub struct JsonValue(pub Value);
+
+impl LuaApi for JsonValue {
+    const CLASS_NAME: &'static str = "JsonValue";
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("to_lua", |lua, this, ()| {
+            LuaValue::try_from_json(lua, &this.0).into_lua_err()
+        });
+
+        reg.add_method("to_string", |_, this, ()| {
+            serde_json::to_string(&this.0).into_lua_err()
+        });
+
+        reg.add_method("to_string_pretty", |_, this, ()| {
+            serde_json::to_string_pretty(&this.0).into_lua_err()
+        });
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "from_str",
+            lua.create_function(|lua, str: LuaString| {
+                let value = serde_json::from_slice(str.as_bytes()).into_lua_err()?;
+                lua.create_any_userdata(JsonValue(value))
+            })?,
+        )?;
+        table.set(
+            "from_lua",
+            lua.create_function(|lua, value: LuaValue| {
+                let value = Value::try_json_from(value).into_lua_err()?;
+                lua.create_any_userdata(JsonValue(value))
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
 
 pub fn push_json_api(lua: &Lua, utils_table: &LuaTable) -> LuaResult<()> {
     let json = lua.create_table()?;
@@ -30,6 +72,7 @@ pub fn push_json_api(lua: &Lua, utils_table: &LuaTable) -> LuaResult<()> {
             LuaValue::try_from_json(lua, &json_value).into_lua_err()
         })?,
     )?;
+    JsonValue::push_lua(lua, &json)?;
     utils_table.set("json", json)?;
 
     Ok(())
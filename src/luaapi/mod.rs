@@ -11,3 +11,71 @@ pub mod utf8;
 pub mod utils;
 pub mod worker;
 mod wrappers;
+
+use mlua::prelude::*;
+
+/// Makes `table` throw an error when any of `names` is accessed, or any key if `names` is `None`
+fn forbid_access(
+    lua: &Lua,
+    table: &LuaTable,
+    path: &str,
+    names: Option<&'static [&'static str]>,
+) -> LuaResult<()> {
+    let path = path.to_owned();
+
+    let metatable = lua.create_table()?;
+    metatable.set(
+        "__index",
+        lua.create_function(move |_, (_, key): (LuaTable, LuaValue)| {
+            let LuaValue::String(key) = key else {
+                return Ok(LuaValue::Nil);
+            };
+
+            let key = key.to_string_lossy();
+            if names.map_or(true, |names| names.contains(&&*key)) {
+                Err(LuaError::RuntimeError(format!(
+                    "`{path}.{key}` is only available on the main thread"
+                )))
+            } else {
+                Ok(LuaValue::Nil)
+            }
+        })?,
+    )?;
+    table.set_metatable(Some(metatable));
+
+    Ok(())
+}
+
+/// Pushes every API. Must only be used for the state that runs on the GTK main thread
+pub fn push_main_thread_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+    gdk::push_api(lua, table)?;
+    gio::push_api(lua, table)?;
+    glib::push_api(lua, table)?;
+    gtk::push_api(lua, table)?;
+    hyprland::push_api(lua, table)?;
+    pulseaudio::push_api(lua, table)?;
+    sysinfo::push_api(lua, table)?;
+    utf8::push_api(lua, table)?;
+    utils::push_api(lua, table)?;
+    worker::push_api(lua, table)?;
+
+    Ok(())
+}
+
+/// Pushes APIs that can be used from any thread.
+/// Accessing the rest throws an error instead of crashing on GTK's main thread assertions
+pub fn push_thread_safe_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+    gio::push_thread_safe_api(lua, table)?;
+    glib::push_thread_safe_api(lua, table)?;
+    sysinfo::push_thread_safe_api(lua, table)?;
+    utf8::push_api(lua, table)?;
+    utils::push_api(lua, table)?;
+
+    for name in ["gdk", "gtk", "hyprland", "pulseaudio"] {
+        let stub = lua.create_table()?;
+        forbid_access(lua, &stub, &format!("crabshell.{name}"), None)?;
+        table.set(name, stub)?;
+    }
+
+    Ok(())
+}
@@ -2,10 +2,12 @@ use gtk::glib::MainContext;
 use mlua::{prelude::*, IntoLua};
 use pulse::{
     callbacks::ListResult,
-    context::Context,
+    context::{introspect::ModuleInfo, Context},
+    def::INVALID_INDEX,
     volume::{ChannelVolumes, Volume},
 };
 use pulse_glib::Mainloop;
+use tokio::sync::oneshot;
 
 use crate::{traits::LuaApi, utils::catch_lua_errors};
 
@@ -106,6 +108,36 @@ impl<'lua, 'a> IntoLua<'lua> for ServerInfoWrapper<'a> {
     }
 }
 
+struct ModuleInfoWrapper {
+    index: u32,
+    name: Option<String>,
+    argument: Option<String>,
+    n_used: Option<u32>,
+}
+
+impl<'a> From<&ModuleInfo<'a>> for ModuleInfoWrapper {
+    fn from(info: &ModuleInfo<'a>) -> Self {
+        Self {
+            index: info.index,
+            name: info.name.as_deref().map(str::to_owned),
+            argument: info.argument.as_deref().map(str::to_owned),
+            n_used: info.n_used,
+        }
+    }
+}
+
+impl<'lua> IntoLua<'lua> for ModuleInfoWrapper {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        let table = lua.create_table_with_capacity(0, 4)?;
+        copy_field!(table, self, index);
+        copy_field!(table, self, name);
+        copy_field!(table, self, argument);
+        copy_field!(table, self, n_used);
+
+        Ok(LuaValue::Table(table))
+    }
+}
+
 fn push_enums(lua: &Lua, pulseaudio_table: &LuaTable) -> LuaResult<()> {
     push_enum!(
         lua,
@@ -313,6 +345,71 @@ impl LuaApi for Context {
                 Ok(())
             },
         );
+
+        reg.add_async_method("get_module_info_list", |lua, this, ()| {
+            let (tx, rx) = oneshot::channel();
+            let mut tx = Some(tx);
+            let mut modules = Vec::new();
+            this.introspect()
+                .get_module_info_list(move |result| match result {
+                    ListResult::Item(item) => modules.push(ModuleInfoWrapper::from(item)),
+                    ListResult::End => {
+                        if let Some(tx) = tx.take() {
+                            let _ = tx.send(Some(std::mem::take(&mut modules)));
+                        }
+                    }
+                    ListResult::Error => {
+                        if let Some(tx) = tx.take() {
+                            let _ = tx.send(None);
+                        }
+                    }
+                });
+
+            async move {
+                match rx.await.into_lua_err()? {
+                    Some(modules) => lua.create_sequence_from(modules),
+                    None => Err(this.errno()).into_lua_err(),
+                }
+            }
+        });
+
+        reg.add_async_method(
+            "load_module",
+            |_, this, (name, argument): (String, Option<String>)| {
+                let (tx, rx) = oneshot::channel();
+                let mut tx = Some(tx);
+                this.introspect().load_module(
+                    &name,
+                    argument.as_deref().unwrap_or(""),
+                    move |index| {
+                        if let Some(tx) = tx.take() {
+                            let _ = tx.send(index);
+                        }
+                    },
+                );
+
+                async move {
+                    let index = rx.await.into_lua_err()?;
+                    if index == INVALID_INDEX {
+                        Err(this.errno()).into_lua_err()
+                    } else {
+                        Ok(index)
+                    }
+                }
+            },
+        );
+
+        reg.add_async_method("unload_module", |_, this, index: u32| {
+            let (tx, rx) = oneshot::channel();
+            let mut tx = Some(tx);
+            this.introspect().unload_module(index, move |success| {
+                if let Some(tx) = tx.take() {
+                    let _ = tx.send(success);
+                }
+            });
+
+            async move { rx.await.into_lua_err() }
+        });
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -1,13 +1,134 @@
 use mlua::prelude::*;
-use sysinfo::{Cpu, CpuExt, NetworkExt, Pid, System, SystemExt};
+use std::time::Duration;
+use sysinfo::{
+    ComponentExt, Cpu, CpuExt, DiskExt, NetworkExt, Pid, PidExt, Process, ProcessExt, System,
+    SystemExt, UserExt,
+};
 
-use super::wrappers::RefreshKindWrapper;
+use super::{forbid_access, wrappers::RefreshKindWrapper};
 use crate::{
     luaapi::wrappers::{CpuRefreshKindWrapper, ProcessRefreshKindWrapper},
-    system_info::battery,
+    system_info::{
+        backlight::{self, BacklightInfo, BacklightMonitor},
+        battery,
+        battery_monitor::{BatteryEvent, BatteryMonitor},
+        cpufreq, pressure,
+        sampler::{Sample, Sampler},
+        sensors,
+    },
     traits::LuaApi,
+    utils::catch_lua_errors,
 };
 
+/// Signals are exposed with their POSIX numbers, so raw numbers can be passed too
+macro_rules! signals {
+    ([$(($name:ident, $signal:ident)),+]) => {
+        const SIGNALS: &[(&str, libc::c_int)] = &[$((stringify!($name), libc::$signal)),+];
+
+        fn push_signals(lua: &Lua, sysinfo_table: &LuaTable) -> LuaResult<()> {
+            let signal_table = lua.create_table()?;
+            $(signal_table.set(stringify!($name), libc::$signal)?;)+
+            sysinfo_table.set("Signal", signal_table)?;
+
+            Ok(())
+        }
+    };
+}
+
+signals!([
+    (Hangup, SIGHUP),
+    (Interrupt, SIGINT),
+    (Quit, SIGQUIT),
+    (Illegal, SIGILL),
+    (Trap, SIGTRAP),
+    (Abort, SIGABRT),
+    (IOT, SIGIOT),
+    (Bus, SIGBUS),
+    (FloatingPointException, SIGFPE),
+    (Kill, SIGKILL),
+    (User1, SIGUSR1),
+    (Segv, SIGSEGV),
+    (User2, SIGUSR2),
+    (Pipe, SIGPIPE),
+    (Alarm, SIGALRM),
+    (Term, SIGTERM),
+    (Child, SIGCHLD),
+    (Continue, SIGCONT),
+    (Stop, SIGSTOP),
+    (TSTP, SIGTSTP),
+    (TTIN, SIGTTIN),
+    (TTOU, SIGTTOU),
+    (Urgent, SIGURG),
+    (XCPU, SIGXCPU),
+    (XFSZ, SIGXFSZ),
+    (VirtualAlarm, SIGVTALRM),
+    (Profiling, SIGPROF),
+    (Winch, SIGWINCH),
+    (IO, SIGIO),
+    (Poll, SIGPOLL),
+    (Power, SIGPWR),
+    (Sys, SIGSYS)
+]);
+
+/// POSIX signal number, accepted as a number or a `Signal` name
+struct SignalWrapper(libc::c_int);
+impl<'lua> FromLua<'lua> for SignalWrapper {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        let signal = match &value {
+            LuaValue::Nil => Some(libc::SIGKILL),
+            LuaValue::Integer(number) => SIGNALS
+                .iter()
+                .find(|&&(_, signal)| LuaInteger::from(signal) == *number)
+                .map(|&(_, signal)| signal),
+            LuaValue::String(name) => {
+                let name = name.to_str()?;
+                SIGNALS
+                    .iter()
+                    .find(|&&(signal_name, _)| signal_name == name)
+                    .map(|&(_, signal)| signal)
+            }
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "Signal",
+                    message: None,
+                })
+            }
+        };
+
+        signal
+            .map(Self)
+            .ok_or_else(|| LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "Signal",
+                message: Some(format!("Unknown signal {:?}", value)),
+            })
+    }
+}
+
+fn push_process<'lua>(lua: &'lua Lua, process: &Process) -> LuaResult<LuaTable<'lua>> {
+    let table = lua.create_table_with_capacity(0, 10)?;
+    table.set("pid", process.pid().as_u32())?;
+    table.set("name", process.name())?;
+    table.set("cmd", process.cmd())?;
+    table.set("exe", process.exe().to_string_lossy())?;
+    table.set("cpu_usage", process.cpu_usage())?;
+    table.set("memory", process.memory())?;
+    table.set("status", process.status().to_string())?;
+    table.set("start_time", process.start_time())?;
+    table.set("parent", process.parent().map(|pid| pid.as_u32()))?;
+
+    let disk_usage = process.disk_usage();
+    let disk_usage_table = lua.create_table_with_capacity(0, 4)?;
+    disk_usage_table.set("total_written_bytes", disk_usage.total_written_bytes)?;
+    disk_usage_table.set("written_bytes", disk_usage.written_bytes)?;
+    disk_usage_table.set("total_read_bytes", disk_usage.total_read_bytes)?;
+    disk_usage_table.set("read_bytes", disk_usage.read_bytes)?;
+    table.set("disk_usage", disk_usage_table)?;
+
+    Ok(table)
+}
+
 impl LuaApi for System {
     const CLASS_NAME: &'static str = "System";
 
@@ -127,6 +248,30 @@ impl LuaApi for System {
             Ok(cpu_table)
         });
 
+        reg.add_method("cpufreq", |lua, _, ()| {
+            let cpus = cpufreq::get_cpufreq().into_lua_err()?;
+            lua.to_value(&cpus)
+        });
+
+        reg.add_method(
+            "set_governor",
+            |_, _, (governor, cpu): (String, Option<u32>)| {
+                cpufreq::set_governor(cpu, &governor).into_lua_err()
+            },
+        );
+
+        reg.add_method(
+            "set_energy_performance_preference",
+            |_, _, (preference, cpu): (String, Option<u32>)| {
+                cpufreq::set_energy_performance_preference(cpu, &preference).into_lua_err()
+            },
+        );
+
+        reg.add_method("pressure", |lua, _, ()| {
+            let pressure = pressure::get_pressure().into_lua_err()?;
+            lua.to_value(&pressure)
+        });
+
         reg.add_method("total_memory", |_, this, ()| {
             Ok(LuaValue::Integer(this.total_memory() as i64))
         });
@@ -184,6 +329,107 @@ impl LuaApi for System {
 
             Ok(networks_table)
         });
+
+        reg.add_method("load_average", |lua, this, ()| {
+            let load_average = this.load_average();
+            let load_average_table = lua.create_table_with_capacity(0, 3)?;
+            load_average_table.set("one", load_average.one)?;
+            load_average_table.set("five", load_average.five)?;
+            load_average_table.set("fifteen", load_average.fifteen)?;
+
+            Ok(load_average_table)
+        });
+
+        reg.add_method("uptime", |_, this, ()| Ok(this.uptime()));
+        reg.add_method("boot_time", |_, this, ()| Ok(this.boot_time()));
+        reg.add_method("host_name", |_, this, ()| Ok(this.host_name()));
+        reg.add_method("kernel_version", |_, this, ()| Ok(this.kernel_version()));
+        reg.add_method("os_version", |_, this, ()| Ok(this.os_version()));
+        reg.add_method("long_os_version", |_, this, ()| Ok(this.long_os_version()));
+        reg.add_method("distribution_id", |_, this, ()| Ok(this.distribution_id()));
+
+        reg.add_method("disks", |lua, this, ()| {
+            let disks = this.disks();
+            let disks_table = lua.create_table_with_capacity(disks.len(), 0)?;
+            for disk in disks {
+                let disk_table = lua.create_table_with_capacity(0, 6)?;
+                disk_table.set("name", disk.name().to_string_lossy())?;
+                disk_table.set("mount_point", disk.mount_point().to_string_lossy())?;
+                disk_table.set(
+                    "file_system",
+                    String::from_utf8_lossy(disk.file_system()).as_ref(),
+                )?;
+                disk_table.set("total_space", disk.total_space())?;
+                disk_table.set("available_space", disk.available_space())?;
+                disk_table.set("is_removable", disk.is_removable())?;
+                disks_table.push(disk_table)?;
+            }
+
+            Ok(disks_table)
+        });
+
+        reg.add_method("components", |lua, this, ()| {
+            let components = this.components();
+            let components_table = lua.create_table_with_capacity(components.len(), 0)?;
+            for component in components {
+                let component_table = lua.create_table_with_capacity(0, 4)?;
+                component_table.set("label", component.label())?;
+                component_table.set("temperature", component.temperature())?;
+                component_table.set("max", component.max())?;
+                component_table.set("critical", component.critical())?;
+                components_table.push(component_table)?;
+            }
+
+            Ok(components_table)
+        });
+
+        reg.add_method("users", |lua, this, ()| {
+            let users = this.users();
+            let users_table = lua.create_table_with_capacity(users.len(), 0)?;
+            for user in users {
+                let user_table = lua.create_table_with_capacity(0, 2)?;
+                user_table.set("name", user.name())?;
+                user_table.set("groups", user.groups())?;
+                users_table.push(user_table)?;
+            }
+
+            Ok(users_table)
+        });
+
+        reg.add_method("processes", |lua, this, ()| {
+            let processes = this.processes();
+            let processes_table = lua.create_table_with_capacity(processes.len(), 0)?;
+            for process in processes.values() {
+                processes_table.push(push_process(lua, process)?)?;
+            }
+
+            Ok(processes_table)
+        });
+
+        reg.add_method("process", |lua, this, pid: usize| {
+            Ok(if let Some(process) = this.process(Pid::from(pid)) {
+                Some(push_process(lua, process)?)
+            } else {
+                None
+            })
+        });
+
+        reg.add_method("processes_by_name", |lua, this, name: String| {
+            let processes_table = lua.create_table()?;
+            for process in this.processes_by_name(&name) {
+                processes_table.push(push_process(lua, process)?)?;
+            }
+
+            Ok(processes_table)
+        });
+
+        // Signals the pid directly, so that it doesn't depend on when processes were refreshed
+        reg.add_method(
+            "kill",
+            |_, _, (pid, signal): (libc::pid_t, SignalWrapper)| {
+                Ok(pid > 0 && unsafe { libc::kill(pid, signal.0) } == 0)
+            },
+        );
     }
 
     fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
@@ -206,32 +452,375 @@ impl LuaApi for System {
     }
 }
 
-fn push_battery_api(lua: &Lua, sysinfo_table: &LuaTable) -> LuaResult<()> {
+impl LuaApi for BatteryMonitor {
+    const CLASS_NAME: &'static str = "BatteryMonitor";
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!(
+            "BatteryMonitor {{ running = {}, uevent = {} }}",
+            self.running(),
+            self.uses_uevent()
+        ))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method_mut("start", |_, this, force_polling: Option<bool>| {
+            this.start(force_polling.unwrap_or(false));
+            Ok(())
+        });
+
+        reg.add_method_mut("stop", |_, this, ()| {
+            this.stop();
+            Ok(())
+        });
+
+        reg.add_method("refresh", |_, this, ()| {
+            this.refresh();
+            Ok(())
+        });
+
+        reg.add_method("running", |_, this, ()| Ok(this.running()));
+        reg.add_method("uses_uevent", |_, this, ()| Ok(this.uses_uevent()));
+
+        reg.add_method("set_thresholds", |_, this, thresholds: Vec<i32>| {
+            this.set_thresholds(thresholds);
+            Ok(())
+        });
+
+        reg.add_method("connect_ac_changed", |_, this, f: LuaOwnedFunction| {
+            this.connect(move |event| {
+                if let BatteryEvent::AcChanged { online } = event {
+                    catch_lua_errors::<_, ()>(f.to_ref(), *online);
+                }
+            });
+            Ok(())
+        });
+
+        reg.add_method("connect_battery_changed", |_, this, f: LuaOwnedFunction| {
+            this.connect(move |event| {
+                if let BatteryEvent::BatteryChanged { name, present } = event {
+                    catch_lua_errors::<_, ()>(f.to_ref(), (name.as_str(), *present));
+                }
+            });
+            Ok(())
+        });
+
+        reg.add_method("connect_status_changed", |_, this, f: LuaOwnedFunction| {
+            this.connect(move |event| {
+                if let BatteryEvent::StatusChanged {
+                    name,
+                    status,
+                    previous,
+                } = event
+                {
+                    catch_lua_errors::<_, ()>(
+                        f.to_ref(),
+                        (name.as_str(), status.as_str(), previous.as_str()),
+                    );
+                }
+            });
+            Ok(())
+        });
+
+        reg.add_method(
+            "connect_threshold_crossed",
+            |_, this, f: LuaOwnedFunction| {
+                this.connect(move |event| {
+                    if let BatteryEvent::ThresholdCrossed {
+                        name,
+                        threshold,
+                        capacity,
+                        falling,
+                    } = event
+                    {
+                        catch_lua_errors::<_, ()>(
+                            f.to_ref(),
+                            (name.as_str(), *threshold, *capacity, *falling),
+                        );
+                    }
+                });
+                Ok(())
+            },
+        );
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, options: Option<LuaTable>| {
+                let mut thresholds = Vec::new();
+                let mut interval = 30.0;
+                if let Some(options) = options {
+                    thresholds = options
+                        .get::<_, Option<Vec<i32>>>("thresholds")?
+                        .unwrap_or_default();
+                    interval = options
+                        .get::<_, Option<f64>>("interval")?
+                        .unwrap_or(interval);
+                }
+
+                let interval = Duration::try_from_secs_f64(interval).into_lua_err()?;
+                lua.create_any_userdata(BatteryMonitor::new(None, thresholds, interval))
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+struct BacklightInfoWrapper<'a>(&'a BacklightInfo);
+impl<'lua, 'a> IntoLua<'lua> for BacklightInfoWrapper<'a> {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        lua.to_value(self.0)
+    }
+}
+
+impl LuaApi for BacklightMonitor {
+    const CLASS_NAME: &'static str = "BacklightMonitor";
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!(
+            "BacklightMonitor {{ running = {} }}",
+            self.running()
+        ))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method_mut("start", |_, this, ()| {
+            this.start();
+            Ok(())
+        });
+
+        reg.add_method_mut("stop", |_, this, ()| {
+            this.stop();
+            Ok(())
+        });
+
+        reg.add_method("refresh", |_, this, ()| {
+            this.refresh();
+            Ok(())
+        });
+
+        reg.add_method("running", |_, this, ()| Ok(this.running()));
+
+        reg.add_method("connect_changed", |_, this, f: LuaOwnedFunction| {
+            this.connect(move |info| {
+                catch_lua_errors::<_, ()>(f.to_ref(), BacklightInfoWrapper(info));
+            });
+            Ok(())
+        });
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, options: Option<LuaTable>| {
+                let mut interval = 1.0;
+                if let Some(options) = options {
+                    interval = options
+                        .get::<_, Option<f64>>("interval")?
+                        .unwrap_or(interval);
+                }
+
+                let interval = Duration::try_from_secs_f64(interval).into_lua_err()?;
+                lua.create_any_userdata(BacklightMonitor::new(None, interval))
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+struct SampleWrapper<'a>(&'a Sample);
+impl<'lua, 'a> IntoLua<'lua> for SampleWrapper<'a> {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        lua.to_value(self.0)
+    }
+}
+
+impl LuaApi for Sampler {
+    const CLASS_NAME: &'static str = "Sampler";
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!("Sampler {{ running = {} }}", self.running()))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method_mut("start", |_, this, ()| {
+            this.start();
+            Ok(())
+        });
+
+        reg.add_method_mut("stop", |_, this, ()| {
+            this.stop();
+            Ok(())
+        });
+
+        reg.add_method("running", |_, this, ()| Ok(this.running()));
+
+        reg.add_method("connect_sample", |_, this, f: LuaOwnedFunction| {
+            this.connect(move |sample| {
+                catch_lua_errors::<_, ()>(f.to_ref(), SampleWrapper(sample));
+            });
+            Ok(())
+        });
+
+        reg.add_method("history", |lua, this, ()| {
+            let history = this.history();
+            let history_table = lua.create_table_with_capacity(history.len(), 0)?;
+            for sample in history.iter() {
+                history_table.push(SampleWrapper(sample))?;
+            }
+
+            Ok(history_table)
+        });
+
+        reg.add_method("latest", |lua, this, ()| {
+            Ok(if let Some(sample) = this.latest() {
+                Some(SampleWrapper(&sample).into_lua(lua)?)
+            } else {
+                None
+            })
+        });
+
+        reg.add_method("clear_history", |_, this, ()| {
+            this.clear_history();
+            Ok(())
+        });
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(|lua, options: Option<LuaTable>| {
+                let mut interval = 1.0;
+                let mut history_size = 60;
+                if let Some(options) = options {
+                    interval = options
+                        .get::<_, Option<f64>>("interval")?
+                        .unwrap_or(interval);
+                    history_size = options
+                        .get::<_, Option<usize>>("history")?
+                        .unwrap_or(history_size);
+                }
+
+                let interval = Duration::try_from_secs_f64(interval).into_lua_err()?;
+                lua.create_any_userdata(Sampler::new(interval, history_size))
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+fn push_battery_api(lua: &Lua, sysinfo_table: &LuaTable, main_thread: bool) -> LuaResult<()> {
     let battery_table = lua.create_table()?;
     battery_table.set(
         "is_on_ac",
-        lua.create_function(|_, ()| Ok(battery::is_on_ac()))?,
+        lua.create_function(|_, ()| battery::is_on_ac().into_lua_err())?,
     )?;
     battery_table.set(
         "get_batteries",
         lua.create_function(|lua, ()| {
-            let batteries = battery::get_batteries();
+            let batteries = battery::get_batteries().into_lua_err()?;
             lua.to_value(&batteries)
         })?,
     )?;
 
+    if main_thread {
+        BatteryMonitor::push_lua(lua, &battery_table)?;
+    } else {
+        forbid_access(
+            lua,
+            &battery_table,
+            "crabshell.sysinfo.battery",
+            Some(&["BatteryMonitor"]),
+        )?;
+    }
+
     sysinfo_table.set("battery", battery_table)?;
 
     Ok(())
 }
 
-pub fn push_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+fn push_backlight_api(lua: &Lua, sysinfo_table: &LuaTable, main_thread: bool) -> LuaResult<()> {
+    let backlight_table = lua.create_table()?;
+    backlight_table.set(
+        "get_backlights",
+        lua.create_function(|lua, ()| {
+            let backlights = backlight::get_backlights().into_lua_err()?;
+            lua.to_value(&backlights)
+        })?,
+    )?;
+    backlight_table.set(
+        "get_backlight",
+        lua.create_function(|lua, name: String| {
+            let info = backlight::get_backlight(&name).into_lua_err()?;
+            lua.to_value(&info)
+        })?,
+    )?;
+    backlight_table.set(
+        "set_brightness",
+        lua.create_async_function(|_, (name, value): (String, u32)| async move {
+            backlight::set_brightness(&name, value).await.into_lua_err()
+        })?,
+    )?;
+
+    if main_thread {
+        BacklightMonitor::push_lua(lua, &backlight_table)?;
+    } else {
+        forbid_access(
+            lua,
+            &backlight_table,
+            "crabshell.sysinfo.backlight",
+            Some(&["BacklightMonitor"]),
+        )?;
+    }
+
+    sysinfo_table.set("backlight", backlight_table)?;
+
+    Ok(())
+}
+
+fn push_sensors_api(lua: &Lua, sysinfo_table: &LuaTable) -> LuaResult<()> {
+    let sensors_table = lua.create_table()?;
+    sensors_table.set(
+        "get_sensors",
+        lua.create_function(|lua, ()| {
+            let sensors = sensors::get_sensors().into_lua_err()?;
+            lua.to_value(&sensors)
+        })?,
+    )?;
+
+    sysinfo_table.set("sensors", sensors_table)?;
+
+    Ok(())
+}
+
+/// Monitors and `Sampler` are driven by the main loop, so they're only pushed if `main_thread` is set
+fn push_sysinfo_api(lua: &Lua, table: &LuaTable, main_thread: bool) -> LuaResult<()> {
     let sysinfo_table = lua.create_table()?;
 
+    push_signals(lua, &sysinfo_table)?;
     System::push_lua(lua, &sysinfo_table)?;
-    push_battery_api(lua, &sysinfo_table)?;
+    if main_thread {
+        Sampler::push_lua(lua, &sysinfo_table)?;
+    } else {
+        forbid_access(lua, &sysinfo_table, "crabshell.sysinfo", Some(&["Sampler"]))?;
+    }
+    push_battery_api(lua, &sysinfo_table, main_thread)?;
+    push_sensors_api(lua, &sysinfo_table)?;
+    push_backlight_api(lua, &sysinfo_table, main_thread)?;
 
     table.set("sysinfo", sysinfo_table)?;
 
     Ok(())
 }
+
+pub fn push_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+    push_sysinfo_api(lua, table, true)
+}
+
+pub fn push_thread_safe_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+    push_sysinfo_api(lua, table, false)
+}
@@ -1,8 +1,21 @@
 use mlua::prelude::LuaError;
+use std::io;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum Error {
     #[error("Lua error")]
     Lua(#[from] LuaError),
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+    #[error("Worker has finished")]
+    Finished,
+    #[error("Module `{0}` not found:{1}")]
+    ModuleNotFound(String, String),
+    #[error("Worker pool has been closed")]
+    PoolClosed,
+    #[error("Task result has already been taken")]
+    ResultTaken,
+    #[error("Task has been dropped by the worker pool")]
+    TaskDropped,
 }
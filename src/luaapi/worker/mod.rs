@@ -1,14 +1,49 @@
 use async_channel::{Receiver, Sender, TryRecvError, TrySendError};
-use mlua::prelude::*;
+use mlua::{prelude::*, Variadic};
+use std::{path::PathBuf, time::Duration};
 
 mod error;
+mod pool;
+mod shared;
+mod transfer;
 
 #[allow(clippy::module_inception)]
 mod worker;
 
 use crate::traits::LuaApi;
 
-use self::worker::{Worker, WorkerData, WorkerEvent};
+use self::{
+    error::Error,
+    pool::{PoolTask, WorkerPool},
+    worker::{ExitReason, Worker, WorkerCode, WorkerData, WorkerEnv, WorkerEvent},
+};
+
+fn worker_args(lua: &Lua, args: LuaMultiValue) -> LuaResult<Vec<WorkerData>> {
+    args.into_iter()
+        .map(|arg| WorkerData::from_lua(arg, lua))
+        .collect()
+}
+
+/// Resolves a module the same way `require` does
+fn find_module(lua: &Lua, name: &str) -> LuaResult<PathBuf> {
+    let package = lua.globals().get::<_, LuaTable>("package")?;
+    let (path, err) =
+        package
+            .get::<_, LuaFunction>("searchpath")?
+            .call::<_, (Option<String>, Option<String>)>((
+                name,
+                package.get::<_, LuaString>("path")?,
+            ))?;
+
+    match path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Err(Error::ModuleNotFound(
+            name.to_owned(),
+            err.unwrap_or_default(),
+        ))
+        .into_lua_err(),
+    }
+}
 
 impl LuaApi for Sender<WorkerData> {
     const CLASS_NAME: &'static str = "Sender<WorkerData>";
@@ -42,19 +77,19 @@ impl LuaApi for Receiver<WorkerEvent> {
 
     fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
         reg.add_async_method("recv", |lua, this, ()| async {
-            Ok(match this.recv().await.into_lua_err()? {
-                WorkerEvent::UserData(data) => data.into_lua(lua)?,
-                WorkerEvent::Error(err) => Err(err)?,
-                WorkerEvent::Done => LuaValue::Nil, // TODO: Throw a error?
-            })
+            match this.recv().await {
+                Ok(WorkerEvent::UserData(data)) => data.into_lua(lua),
+                Ok(WorkerEvent::Error(err)) => Err(err),
+                Ok(WorkerEvent::Done) | Err(_) => Err(Error::Finished).into_lua_err(),
+            }
         });
 
         reg.add_method("recv_blocking", |lua, this, ()| {
-            Ok(match this.recv_blocking().into_lua_err()? {
-                WorkerEvent::UserData(data) => data.into_lua(lua)?,
-                WorkerEvent::Error(err) => Err(err)?,
-                WorkerEvent::Done => LuaValue::Nil, // TODO: Throw a error?
-            })
+            match this.recv_blocking() {
+                Ok(WorkerEvent::UserData(data)) => data.into_lua(lua),
+                Ok(WorkerEvent::Error(err)) => Err(err),
+                Ok(WorkerEvent::Done) | Err(_) => Err(Error::Finished).into_lua_err(),
+            }
         });
 
         reg.add_method("try_recv", |lua, this, ()| {
@@ -63,11 +98,11 @@ impl LuaApi for Receiver<WorkerEvent> {
                     WorkerEvent::UserData(value) => {
                         LuaMultiValue::from_vec(vec![LuaValue::Boolean(true), value.into_lua(lua)?])
                     }
-                    WorkerEvent::Done => Err(TryRecvError::Closed).into_lua_err()?,
+                    WorkerEvent::Done => Err(Error::Finished).into_lua_err()?,
                     WorkerEvent::Error(err) => Err(err)?,
                 },
                 Err(TryRecvError::Empty) => LuaMultiValue::from_vec(vec![LuaValue::Boolean(false)]),
-                Err(err) => Err(err).into_lua_err()?,
+                Err(TryRecvError::Closed) => Err(Error::Finished).into_lua_err()?,
             })
         });
     }
@@ -76,45 +111,41 @@ impl LuaApi for Receiver<WorkerEvent> {
 impl LuaApi for Worker {
     const CLASS_NAME: &'static str = "Worker";
 
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!(
+            "Worker {{ name = {:?}, status = {} }}",
+            self.name(),
+            self.exit_reason().map_or("running", ExitReason::as_str)
+        ))
+    }
+
     fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("name", |_, this, ()| Ok(this.name().to_owned()));
         reg.add_method("dead", |_, this, ()| Ok(this.dead()));
 
-        reg.add_async_method_mut("join", |lua, this, ()| async {
-            if this.dead() {
-                // Prevent deadlocking
-                Ok(LuaValue::Nil)
-            } else {
-                let receiver = this.receiver();
-                let results = lua.create_table()?;
-                loop {
-                    match receiver.recv().await.into_lua_err()? {
-                        WorkerEvent::UserData(data) => results.push(data)?,
-                        WorkerEvent::Error(err) => Err(err).into_lua_err()?,
-                        WorkerEvent::Done => break,
-                    };
-                }
-
-                Ok(LuaValue::Table(results))
-            }
+        reg.add_method("status", |_, this, ()| {
+            Ok(this.exit_reason().map_or("running", ExitReason::as_str))
         });
 
-        reg.add_method_mut("join_blocking", |lua, this, ()| {
-            if this.dead() {
-                // Prevent deadlocking
-                Ok(LuaValue::Nil)
-            } else {
-                let receiver = this.receiver();
-                let results = lua.create_table()?;
-                loop {
-                    match receiver.recv_blocking().into_lua_err()? {
-                        WorkerEvent::UserData(data) => results.push(data)?,
-                        WorkerEvent::Error(err) => Err(err).into_lua_err()?,
-                        WorkerEvent::Done => break,
-                    };
-                }
-
-                Ok(LuaValue::Table(results))
-            }
+        reg.add_method("terminate", |_, this, ()| {
+            this.terminate();
+            Ok(())
+        });
+
+        reg.add_async_method_mut("join", |_, this, timeout: Option<f64>| async move {
+            let timeout = timeout
+                .map(Duration::try_from_secs_f64)
+                .transpose()
+                .into_lua_err()?;
+            this.join(timeout).await
+        });
+
+        reg.add_method_mut("join_blocking", |_, this, timeout: Option<f64>| {
+            let timeout = timeout
+                .map(Duration::try_from_secs_f64)
+                .transpose()
+                .into_lua_err()?;
+            this.join_blocking(timeout)
         });
 
         reg.add_method_mut("sender", |lua, this, ()| {
@@ -130,13 +161,201 @@ impl LuaApi for Worker {
         table.set(
             "start",
             lua.create_function(
-                |lua, (code, name, channel_size): (String, Option<String>, Option<usize>)| {
-                    let worker = Worker::start(code, name, channel_size).into_lua_err()?;
+                |lua,
+                 (code, name, channel_size, args): (
+                    String,
+                    Option<String>,
+                    Option<usize>,
+                    LuaMultiValue,
+                )| {
+                    let worker = Worker::start(
+                        WorkerCode::String(code),
+                        name,
+                        channel_size,
+                        WorkerEnv::inherit(lua)?,
+                        worker_args(lua, args)?,
+                    )
+                    .into_lua_err()?;
                     lua.create_any_userdata(worker)
                 },
             )?,
         )?;
 
+        table.set(
+            "start_file",
+            lua.create_function(|lua, (path, args): (String, LuaMultiValue)| {
+                let worker = Worker::start(
+                    WorkerCode::File(PathBuf::from(&path)),
+                    Some(path),
+                    None,
+                    WorkerEnv::inherit(lua)?,
+                    worker_args(lua, args)?,
+                )
+                .into_lua_err()?;
+                lua.create_any_userdata(worker)
+            })?,
+        )?;
+
+        table.set(
+            "start_module",
+            lua.create_function(|lua, (name, args): (String, LuaMultiValue)| {
+                let worker = Worker::start(
+                    WorkerCode::File(find_module(lua, &name)?),
+                    Some(name),
+                    None,
+                    WorkerEnv::inherit(lua)?,
+                    worker_args(lua, args)?,
+                )
+                .into_lua_err()?;
+                lua.create_any_userdata(worker)
+            })?,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl LuaApi for PoolTask {
+    const CLASS_NAME: &'static str = "PoolTask";
+    const CONSTRUCTIBLE: bool = false;
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("done", |_, this, ()| Ok(this.done()));
+
+        reg.add_async_method_mut("result", |_, this, ()| async move {
+            Ok(Variadic::from_iter(this.result().await?))
+        });
+
+        reg.add_method_mut("result_blocking", |_, this, ()| {
+            Ok(Variadic::from_iter(this.result_blocking()?))
+        });
+    }
+}
+
+impl LuaApi for WorkerPool {
+    const CLASS_NAME: &'static str = "WorkerPool";
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!(
+            "WorkerPool {{ name = {:?}, size = {}, queued = {} }}",
+            self.name(),
+            self.size(),
+            self.queued()
+        ))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_method("name", |_, this, ()| Ok(this.name().to_owned()));
+        reg.add_method("size", |_, this, ()| Ok(this.size()));
+        reg.add_method("queued", |_, this, ()| Ok(this.queued()));
+        reg.add_method("closed", |_, this, ()| Ok(this.closed()));
+
+        reg.add_method("close", |_, this, ()| {
+            this.close();
+            Ok(())
+        });
+
+        reg.add_async_method(
+            "submit",
+            |lua, this, (function, args): (String, LuaMultiValue)| async move {
+                let task = this
+                    .submit(function, worker_args(lua, args)?)
+                    .await
+                    .into_lua_err()?;
+                lua.create_any_userdata(task)
+            },
+        );
+
+        reg.add_method(
+            "submit_blocking",
+            |lua, this, (function, args): (String, LuaMultiValue)| {
+                let task = this
+                    .submit_blocking(function, worker_args(lua, args)?)
+                    .into_lua_err()?;
+                lua.create_any_userdata(task)
+            },
+        );
+
+        reg.add_method(
+            "try_submit",
+            |lua, this, (function, args): (String, LuaMultiValue)| {
+                this.try_submit(function, worker_args(lua, args)?)
+                    .into_lua_err()?
+                    .map(|task| lua.create_any_userdata(task))
+                    .transpose()
+            },
+        );
+
+        reg.add_async_method(
+            "call",
+            |lua, this, (function, args): (String, LuaMultiValue)| async move {
+                let mut task = this
+                    .submit(function, worker_args(lua, args)?)
+                    .await
+                    .into_lua_err()?;
+                Ok(Variadic::from_iter(task.result().await?))
+            },
+        );
+    }
+
+    fn register_static_methods(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
+        table.set(
+            "new",
+            lua.create_function(
+                |lua,
+                 (code, size, queue_size, name): (
+                    String,
+                    Option<usize>,
+                    Option<usize>,
+                    Option<String>,
+                )| {
+                    let pool = WorkerPool::new(
+                        WorkerCode::String(code),
+                        name,
+                        size,
+                        queue_size,
+                        WorkerEnv::inherit(lua)?,
+                    )
+                    .into_lua_err()?;
+                    lua.create_any_userdata(pool)
+                },
+            )?,
+        )?;
+
+        table.set(
+            "from_file",
+            lua.create_function(
+                |lua, (path, size, queue_size): (String, Option<usize>, Option<usize>)| {
+                    let pool = WorkerPool::new(
+                        WorkerCode::File(PathBuf::from(&path)),
+                        Some(path),
+                        size,
+                        queue_size,
+                        WorkerEnv::inherit(lua)?,
+                    )
+                    .into_lua_err()?;
+                    lua.create_any_userdata(pool)
+                },
+            )?,
+        )?;
+
+        table.set(
+            "from_module",
+            lua.create_function(
+                |lua, (name, size, queue_size): (String, Option<usize>, Option<usize>)| {
+                    let pool = WorkerPool::new(
+                        WorkerCode::File(find_module(lua, &name)?),
+                        Some(name),
+                        size,
+                        queue_size,
+                        WorkerEnv::inherit(lua)?,
+                    )
+                    .into_lua_err()?;
+                    lua.create_any_userdata(pool)
+                },
+            )?,
+        )?;
+
         Ok(())
     }
 }
@@ -147,6 +366,9 @@ pub fn push_api(lua: &Lua, table: &LuaTable) -> LuaResult<()> {
     Sender::<WorkerData>::push_lua(lua, &worker_table)?;
     Receiver::<WorkerEvent>::push_lua(lua, &worker_table)?;
     Worker::push_lua(lua, &worker_table)?;
+    PoolTask::push_lua(lua, &worker_table)?;
+    WorkerPool::push_lua(lua, &worker_table)?;
+    shared::push_api(lua, &worker_table)?;
 
     table.set("worker", worker_table)?;
 
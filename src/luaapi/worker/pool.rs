@@ -0,0 +1,281 @@
+use async_channel::{Receiver, Sender, TrySendError};
+use mlua::{prelude::*, Variadic};
+use std::{num::NonZeroUsize, thread};
+
+use super::{
+    error::Error,
+    worker::{format_error, Worker, WorkerCode, WorkerData, WorkerEnv},
+};
+use crate::{error::LuaErrorWrapper, traits::LuaExt};
+
+/// Values returned by a task, or its error message with a traceback
+type TaskResult = Result<Vec<WorkerData>, String>;
+
+struct Task {
+    function: String,
+    args: Vec<WorkerData>,
+    result: Sender<TaskResult>,
+}
+
+/// Functions available to tasks in a pool thread
+struct Runtime<'lua> {
+    functions: LuaTable<'lua>,
+    xpcall: LuaFunction<'lua>,
+    traceback: LuaFunction<'lua>,
+}
+
+/// Splits results of `xpcall` into the error message or returned values
+fn xpcall_results(
+    results: LuaMultiValue<'_>,
+) -> Result<impl Iterator<Item = LuaValue<'_>>, String> {
+    let mut results = results.into_iter();
+    match results.next() {
+        Some(LuaValue::Boolean(true)) => Ok(results),
+        _ => Err(format_error(results.next().unwrap_or(LuaValue::Nil))),
+    }
+}
+
+/// Pending result of a task submitted to a `WorkerPool`
+pub struct PoolTask {
+    pool_name: String,
+    function: String,
+    receiver: Receiver<TaskResult>,
+    taken: bool,
+}
+
+impl PoolTask {
+    /// Returns whether the result is available without waiting
+    pub fn done(&self) -> bool {
+        self.taken || !self.receiver.is_empty() || self.receiver.is_closed()
+    }
+
+    pub async fn result(&mut self) -> LuaResult<Vec<WorkerData>> {
+        if self.taken {
+            return Err(Error::ResultTaken).into_lua_err();
+        }
+
+        let result = self.receiver.recv().await;
+        self.handle_result(result.ok())
+    }
+
+    /// A blocking version of `result`
+    pub fn result_blocking(&mut self) -> LuaResult<Vec<WorkerData>> {
+        if self.taken {
+            return Err(Error::ResultTaken).into_lua_err();
+        }
+
+        let result = self.receiver.recv_blocking();
+        self.handle_result(result.ok())
+    }
+
+    fn handle_result(&mut self, result: Option<TaskResult>) -> LuaResult<Vec<WorkerData>> {
+        self.taken = true;
+        match result {
+            Some(Ok(values)) => Ok(values),
+            Some(Err(message)) => Err(LuaError::RuntimeError(format!(
+                "Task `{}` of worker pool `{}` failed:\n{message}",
+                self.function, self.pool_name
+            ))),
+            // Pool thread has panicked
+            None => Err(Error::TaskDropped).into_lua_err(),
+        }
+    }
+}
+
+/// Fixed set of threads that run functions defined by the same code, each in its own Lua state.
+/// States are created once and reused by all tasks that their thread picks up
+pub struct WorkerPool {
+    name: String,
+    size: usize,
+    tasks: Sender<Task>,
+}
+
+impl WorkerPool {
+    /// Starts `size` threads, which run `code` and wait for tasks.
+    /// Functions are looked up in the table returned by `code`, or in globals if it returns nothing
+    pub fn new(
+        code: WorkerCode,
+        name: Option<String>,
+        size: Option<usize>,
+        queue_size: Option<usize>,
+        env: WorkerEnv,
+    ) -> Result<Self, Error> {
+        let name = name.unwrap_or_else(|| String::from("worker-pool"));
+        let size = size
+            .unwrap_or_else(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+            .max(1);
+
+        let (tx, rx) = async_channel::bounded(queue_size.unwrap_or(32).max(1));
+
+        for index in 0..size {
+            let code = code.clone();
+            let thread_name = name.clone();
+            let env = env.clone();
+            let tasks = rx.clone();
+            thread::Builder::new()
+                .name(format!("{name}-{index}"))
+                .spawn(move || Self::run(code, &thread_name, env, tasks))?;
+        }
+
+        Ok(Self {
+            name,
+            size,
+            tasks: tx,
+        })
+    }
+
+    fn run(code: WorkerCode, name: &str, env: WorkerEnv, tasks: Receiver<Task>) {
+        let lua = unsafe { Lua::new_with_stock_allocator() };
+        let runtime = Self::init(&lua, code, name, &env);
+
+        // Exits once the pool is closed and every queued task is taken
+        while let Ok(task) = tasks.recv_blocking() {
+            let result = match &runtime {
+                Ok(runtime) => Self::exec(&lua, runtime, &task.function, task.args),
+                // Report the initialization error to every task, so that none of them hangs
+                Err(message) => Err(message.clone()),
+            };
+
+            // Task could have been dropped without waiting for its result
+            let _ = task.result.send_blocking(result);
+        }
+    }
+
+    fn init<'lua>(
+        lua: &'lua Lua,
+        code: WorkerCode,
+        name: &str,
+        env: &WorkerEnv,
+    ) -> Result<Runtime<'lua>, String> {
+        let setup = || -> LuaResult<(LuaFunction, LuaFunction)> {
+            lua.load_from_std_lib(LuaStdLib::ALL)?;
+            Worker::setup_env(lua)?;
+            env.apply(lua)?;
+
+            let globals = lua.globals();
+            let xpcall = globals.get::<_, LuaFunction>("xpcall")?;
+            let traceback = globals
+                .get::<_, LuaTable>("debug")?
+                .get::<_, LuaFunction>("traceback")?;
+
+            Ok((xpcall, traceback))
+        };
+        let (xpcall, traceback) = setup().map_err(|err| LuaErrorWrapper(err).to_string())?;
+
+        let func = Worker::load(lua, code, name, env.bytecode_cache)?;
+        let results = xpcall
+            .call::<_, LuaMultiValue>((func, traceback.clone()))
+            .map_err(|err| LuaErrorWrapper(err).to_string())?;
+
+        let functions = match xpcall_results(results)?.next() {
+            Some(LuaValue::Table(table)) => table,
+            _ => lua.globals(),
+        };
+
+        Ok(Runtime {
+            functions,
+            xpcall,
+            traceback,
+        })
+    }
+
+    fn exec(lua: &Lua, runtime: &Runtime, function: &str, args: Vec<WorkerData>) -> TaskResult {
+        let func = match runtime.functions.get::<_, LuaValue>(function) {
+            Ok(LuaValue::Function(func)) => func,
+            Ok(_) => return Err(format!("Function `{function}` is not defined")),
+            Err(err) => return Err(LuaErrorWrapper(err).to_string()),
+        };
+
+        let args = args.into_iter().collect::<Variadic<_>>();
+        let results = runtime
+            .xpcall
+            .call::<_, LuaMultiValue>((func, runtime.traceback.clone(), args))
+            .map_err(|err| LuaErrorWrapper(err).to_string())?;
+
+        xpcall_results(results)?
+            .map(|value| {
+                WorkerData::from_lua(value, lua).map_err(|err| LuaErrorWrapper(err).to_string())
+            })
+            .collect()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Number of tasks waiting for a free thread
+    pub fn queued(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn task(&self, function: String, args: Vec<WorkerData>) -> (Task, PoolTask) {
+        let (tx, rx) = async_channel::bounded(1);
+        let pool_task = PoolTask {
+            pool_name: self.name.clone(),
+            function: function.clone(),
+            receiver: rx,
+            taken: false,
+        };
+
+        let task = Task {
+            function,
+            args,
+            result: tx,
+        };
+
+        (task, pool_task)
+    }
+
+    /// Queues a task, waiting for space in the queue if it's full
+    pub async fn submit(&self, function: String, args: Vec<WorkerData>) -> Result<PoolTask, Error> {
+        let (task, pool_task) = self.task(function, args);
+        self.tasks.send(task).await.map_err(|_| Error::PoolClosed)?;
+        Ok(pool_task)
+    }
+
+    /// A blocking version of `submit`
+    pub fn submit_blocking(
+        &self,
+        function: String,
+        args: Vec<WorkerData>,
+    ) -> Result<PoolTask, Error> {
+        let (task, pool_task) = self.task(function, args);
+        self.tasks
+            .send_blocking(task)
+            .map_err(|_| Error::PoolClosed)?;
+        Ok(pool_task)
+    }
+
+    /// Queues a task, returning `None` if the queue is full
+    pub fn try_submit(
+        &self,
+        function: String,
+        args: Vec<WorkerData>,
+    ) -> Result<Option<PoolTask>, Error> {
+        let (task, pool_task) = self.task(function, args);
+        match self.tasks.try_send(task) {
+            Ok(()) => Ok(Some(pool_task)),
+            Err(TrySendError::Full(_)) => Ok(None),
+            Err(TrySendError::Closed(_)) => Err(Error::PoolClosed),
+        }
+    }
+
+    /// Stops accepting tasks. Threads exit after finishing queued ones
+    pub fn close(&self) {
+        self.tasks.close();
+    }
+
+    pub fn closed(&self) -> bool {
+        self.tasks.is_closed()
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
@@ -0,0 +1,197 @@
+use mlua::prelude::*;
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+use tokio::sync::broadcast::{self, error::TryRecvError};
+
+use super::worker::WorkerData;
+use crate::traits::LuaApi;
+
+/// How many changes a subscriber can fall behind before it starts skipping them
+const CHANGES_CAPACITY: usize = 64;
+
+#[derive(Clone)]
+pub struct Change {
+    pub key: String,
+    /// `Nil` if the key has been removed
+    pub value: WorkerData,
+}
+
+/// Key-value store shared by the main state and every worker
+pub struct SharedStore {
+    values: Mutex<HashMap<String, WorkerData>>,
+    changes: broadcast::Sender<Change>,
+}
+
+impl SharedStore {
+    pub fn global() -> &'static Self {
+        static STORE: OnceLock<SharedStore> = OnceLock::new();
+        STORE.get_or_init(|| Self {
+            values: Mutex::new(HashMap::new()),
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+        })
+    }
+
+    pub fn get(&self, key: &str) -> WorkerData {
+        let values = self.values.lock().unwrap();
+        values.get(key).cloned().unwrap_or(WorkerData::Nil)
+    }
+
+    /// Setting `Nil` removes the key
+    pub fn set(&self, key: String, value: WorkerData) {
+        let mut values = self.values.lock().unwrap();
+        self.replace(&mut values, key, value);
+    }
+
+    /// Sets `value` only if the current one equals `expected`.
+    /// Returns the current value if it doesn't
+    pub fn compare_and_swap(
+        &self,
+        key: String,
+        expected: &WorkerData,
+        value: WorkerData,
+    ) -> Result<(), WorkerData> {
+        let mut values = self.values.lock().unwrap();
+        let current = values.get(&key);
+        if current.unwrap_or(&WorkerData::Nil) != expected {
+            return Err(current.cloned().unwrap_or(WorkerData::Nil));
+        }
+
+        self.replace(&mut values, key, value);
+        Ok(())
+    }
+
+    /// Must be called with the lock held, so that subscribers see changes in the order they're made
+    fn replace(&self, values: &mut HashMap<String, WorkerData>, key: String, value: WorkerData) {
+        if let WorkerData::Nil = value {
+            values.remove(&key);
+        } else {
+            values.insert(key.clone(), value.clone());
+        }
+
+        // Fails only if there are no subscribers
+        let _ = self.changes.send(Change { key, value });
+    }
+
+    /// Subscribes to changes of `key`, or of every key if it's `None`
+    pub fn subscribe(&self, key: Option<String>) -> Subscription {
+        Subscription {
+            key,
+            receiver: self.changes.subscribe(),
+        }
+    }
+}
+
+/// Receives changes made after it has been created
+pub struct Subscription {
+    key: Option<String>,
+    receiver: broadcast::Receiver<Change>,
+}
+
+impl Subscription {
+    fn matches(&self, change: &Change) -> bool {
+        self.key.as_ref().map_or(true, |key| *key == change.key)
+    }
+
+    /// Skips changes that have been dropped because the subscription has lagged behind
+    pub async fn recv(&mut self) -> Change {
+        loop {
+            match self.receiver.recv().await {
+                Ok(change) if self.matches(&change) => return change,
+                // The store is never dropped, so the channel can't be closed
+                _ => continue,
+            }
+        }
+    }
+
+    /// A blocking version of `recv`
+    pub fn recv_blocking(&mut self) -> Change {
+        loop {
+            match self.receiver.blocking_recv() {
+                Ok(change) if self.matches(&change) => return change,
+                _ => continue,
+            }
+        }
+    }
+
+    /// Returns `None` if there are no pending changes
+    pub fn try_recv(&mut self) -> Option<Change> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(change) if self.matches(&change) => return Some(change),
+                Ok(_) | Err(TryRecvError::Lagged(_)) => continue,
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl<'lua> IntoLuaMulti<'lua> for Change {
+    fn into_lua_multi(self, lua: &'lua Lua) -> LuaResult<LuaMultiValue<'lua>> {
+        (self.key, self.value).into_lua_multi(lua)
+    }
+}
+
+impl LuaApi for Subscription {
+    const CLASS_NAME: &'static str = "SharedSubscription";
+    const CONSTRUCTIBLE: bool = false;
+
+    fn to_lua_string<'a>(&self, lua: &'a Lua) -> LuaResult<LuaString<'a>> {
+        lua.create_string(format!("SharedSubscription {{ key = {:?} }}", self.key))
+    }
+
+    fn register_methods(reg: &mut LuaUserDataRegistry<Self>) {
+        reg.add_async_method_mut("recv", |_, this, ()| async move { Ok(this.recv().await) });
+
+        reg.add_method_mut("recv_blocking", |_, this, ()| Ok(this.recv_blocking()));
+
+        reg.add_method_mut("try_recv", |lua, this, ()| match this.try_recv() {
+            Some(change) => (true, change).into_lua_multi(lua),
+            None => false.into_lua_multi(lua),
+        });
+    }
+}
+
+pub fn push_api(lua: &Lua, worker_table: &LuaTable) -> LuaResult<()> {
+    Subscription::push_lua(lua, worker_table)?;
+
+    let shared_table = lua.create_table()?;
+    shared_table.set(
+        "get",
+        lua.create_function(|_, key: String| Ok(SharedStore::global().get(&key)))?,
+    )?;
+
+    shared_table.set(
+        "set",
+        lua.create_function(|lua, (key, value): (String, LuaValue)| {
+            SharedStore::global().set(key, WorkerData::from_lua(value, lua)?);
+            Ok(())
+        })?,
+    )?;
+
+    shared_table.set(
+        "compare_and_swap",
+        lua.create_function(
+            |lua, (key, expected, value): (String, LuaValue, LuaValue)| {
+                let expected = WorkerData::from_lua(expected, lua)?;
+                let value = WorkerData::from_lua(value, lua)?;
+                match SharedStore::global().compare_and_swap(key, &expected, value) {
+                    Ok(()) => true.into_lua_multi(lua),
+                    Err(current) => (false, current).into_lua_multi(lua),
+                }
+            },
+        )?,
+    )?;
+
+    shared_table.set(
+        "subscribe",
+        lua.create_function(|lua, key: Option<String>| {
+            lua.create_any_userdata(SharedStore::global().subscribe(key))
+        })?,
+    )?;
+
+    worker_table.set("shared", shared_table)?;
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use gtk::{gdk::Texture, gio::File, glib::Bytes};
+use mlua::prelude::*;
+
+use crate::luaapi::json::JsonValue;
+
+/// Userdata that can be moved to a Lua state on another thread
+pub trait Transferable: Send + Sync {
+    fn into_lua(self: Box<Self>, lua: &Lua) -> LuaResult<LuaValue<'_>>;
+
+    fn clone_box(&self) -> Box<dyn Transferable>;
+}
+
+impl<T: Clone + Send + Sync + 'static> Transferable for T {
+    fn into_lua(self: Box<Self>, lua: &Lua) -> LuaResult<LuaValue<'_>> {
+        Ok(LuaValue::UserData(lua.create_any_userdata(*self)?))
+    }
+
+    fn clone_box(&self) -> Box<dyn Transferable> {
+        Box::new(self.clone())
+    }
+}
+
+macro_rules! transferable {
+    ([$($typ:ty),+]) => {
+        /// Clones userdata of any of the registered types.
+        /// Userdata keeps its methods only if the receiving state has its type registered
+        pub fn clone_userdata(ud: &LuaAnyUserData) -> Option<Box<dyn Transferable>> {
+            $(
+                if let Ok(value) = ud.borrow::<$typ>() {
+                    return Some(Box::new(value.clone()));
+                }
+            )+
+
+            None
+        }
+    };
+}
+
+// Types listed here must be `Clone + Send + Sync`
+transferable!([Texture, Bytes, File, JsonValue]);
@@ -1,14 +1,26 @@
-use ::gtk::gdk::Texture;
-use async_channel::{Receiver, Sender, TryRecvError, TrySendError};
-use mlua::prelude::*;
-use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc,
+use async_channel::{Receiver, RecvError, Sender, TryRecvError, TrySendError};
+use mlua::{prelude::*, Variadic};
+use std::{
+    cell::RefCell,
+    ffi::{c_int, c_void},
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
-use super::error::Error;
+use super::{
+    error::Error,
+    shared,
+    transfer::{self, Transferable},
+};
 use crate::{
-    luaapi::{gdk, gio, glib, gtk, hyprland, pulseaudio, sysinfo, utf8, utils},
+    bytecode_cache::BytecodeCache,
+    error::LuaErrorWrapper,
+    luaapi,
     traits::{LuaApi, LuaExt},
 };
 
@@ -18,9 +30,51 @@ pub enum WorkerData {
     Number(f64),
     Integer(i64),
     String(String),
+    /// Table with sequential keys only
     Array(Vec<WorkerData>),
+    /// Table with arbitrary keys
+    Map(Vec<(WorkerData, WorkerData)>),
+
+    /// Userdata of one of the types listed in `transfer.rs`
+    UserData(Box<dyn Transferable>),
+}
 
-    Texture(Texture),
+impl Clone for WorkerData {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Nil => Self::Nil,
+            Self::Boolean(value) => Self::Boolean(*value),
+            Self::Number(value) => Self::Number(*value),
+            Self::Integer(value) => Self::Integer(*value),
+            Self::String(str) => Self::String(str.clone()),
+            Self::Array(arr) => Self::Array(arr.clone()),
+            Self::Map(map) => Self::Map(map.clone()),
+            // Not `ud.clone_box()`, which would clone the reference through the blanket impl
+            Self::UserData(ud) => Self::UserData((**ud).clone_box()),
+        }
+    }
+}
+
+impl PartialEq for WorkerData {
+    /// Compares tables by contents, ignoring the order of keys.
+    /// Userdata is never equal to anything, as it's copied on every conversion
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Nil, Self::Nil) => true,
+            (Self::Boolean(a), Self::Boolean(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::Integer(a), Self::Integer(b)) => a == b,
+            (Self::Integer(a), Self::Number(b)) | (Self::Number(b), Self::Integer(a)) => {
+                *a as f64 == *b
+            }
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Map(a), Self::Map(b)) => {
+                a.len() == b.len() && a.iter().all(|entry| b.contains(entry))
+            }
+            _ => false,
+        }
+    }
 }
 
 impl<'lua> IntoLua<'lua> for WorkerData {
@@ -39,13 +93,22 @@ impl<'lua> IntoLua<'lua> for WorkerData {
 
                 LuaValue::Table(result)
             }
-            Self::Texture(texture) => LuaValue::UserData(lua.create_any_userdata(texture)?),
+            Self::Map(map) => {
+                let result = lua.create_table_with_capacity(0, map.len())?;
+                for (key, value) in map {
+                    result.set(key, value)?;
+                }
+
+                LuaValue::Table(result)
+            }
+            Self::UserData(ud) => ud.into_lua(lua)?,
         })
     }
 }
 
-impl<'lua> FromLua<'lua> for WorkerData {
-    fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+impl WorkerData {
+    /// Converts a lua value, keeping track of tables that are being converted to detect cycles
+    fn from_lua_value(value: LuaValue, tables: &mut Vec<*const c_void>) -> LuaResult<Self> {
         match value {
             LuaValue::Nil => Ok(Self::Nil),
             LuaValue::Boolean(value) => Ok(Self::Boolean(value)),
@@ -64,12 +127,20 @@ impl<'lua> FromLua<'lua> for WorkerData {
             }),
             LuaValue::String(str) => Ok(Self::String(String::from(str.to_str()?))),
             LuaValue::Table(table) => {
-                let mut vec = Vec::with_capacity(table.raw_len());
-                for value in table.sequence_values::<LuaValue>() {
-                    vec.push(Self::from_lua(value?, _lua)?);
+                let ptr = table.to_pointer();
+                if tables.contains(&ptr) {
+                    return Err(LuaError::FromLuaConversionError {
+                        from: "table",
+                        to: "WorkerData",
+                        message: Some("recursive tables can't be sent".to_owned()),
+                    });
                 }
 
-                Ok(Self::Array(vec))
+                tables.push(ptr);
+                let result = Self::from_lua_table(table, tables);
+                tables.pop();
+
+                result
             }
             LuaValue::Function(_) => Err(LuaError::FromLuaConversionError {
                 from: "function",
@@ -82,18 +153,49 @@ impl<'lua> FromLua<'lua> for WorkerData {
                 message: None,
             }),
             LuaValue::UserData(ud) => {
-                if let Ok(texture) = ud.take::<Texture>() {
-                    Ok(Self::Texture(texture))
+                if let Some(value) = transfer::clone_userdata(&ud) {
+                    Ok(Self::UserData(value))
                 } else {
                     Err(LuaError::FromLuaConversionError {
                         from: "userdata",
                         to: "WorkerData",
-                        message: None,
+                        message: Some("userdata type can't be sent".to_owned()),
                     })
                 }
             }
         }
     }
+
+    fn from_lua_table(table: LuaTable, tables: &mut Vec<*const c_void>) -> LuaResult<Self> {
+        let array_len = table.raw_len();
+        let total_len = table.clone().pairs::<LuaValue, LuaValue>().count();
+
+        if array_len == total_len {
+            let mut vec = Vec::with_capacity(array_len);
+            for value in table.sequence_values::<LuaValue>() {
+                vec.push(Self::from_lua_value(value?, tables)?);
+            }
+
+            Ok(Self::Array(vec))
+        } else {
+            let mut map = Vec::with_capacity(total_len);
+            for pair in table.pairs::<LuaValue, LuaValue>() {
+                let (key, value) = pair?;
+                map.push((
+                    Self::from_lua_value(key, tables)?,
+                    Self::from_lua_value(value, tables)?,
+                ));
+            }
+
+            Ok(Self::Map(map))
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for WorkerData {
+    fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+        Self::from_lua_value(value, &mut Vec::new())
+    }
 }
 
 impl LuaApi for Sender<WorkerEvent> {
@@ -138,7 +240,7 @@ impl LuaApi for Receiver<WorkerData> {
         reg.add_method("try_recv", |lua, this, ()| {
             Ok(match this.try_recv() {
                 Ok(value) => {
-                    LuaMultiValue::from_vec(vec![LuaValue::Boolean(false), value.into_lua(lua)?])
+                    LuaMultiValue::from_vec(vec![LuaValue::Boolean(true), value.into_lua(lua)?])
                 }
                 Err(TryRecvError::Empty) => LuaMultiValue::from_vec(vec![LuaValue::Boolean(false)]),
                 Err(err) => Err(err).into_lua_err()?,
@@ -153,53 +255,388 @@ pub enum WorkerEvent {
     Done,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Finished,
+    Terminated,
+    Failed,
+}
+
+impl ExitReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Finished => "finished",
+            Self::Terminated => "terminated",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// How many instructions the worker executes between checks of the termination flag
+const TERMINATE_CHECK_INTERVAL: c_int = 1000;
+
+thread_local! {
+    /// Termination flag of the worker running on the current thread
+    static TERMINATED: RefCell<Option<Arc<AtomicBool>>> = const { RefCell::new(None) };
+}
+
+unsafe extern "C-unwind" fn terminate_hook(
+    state: *mut mlua_sys::lua_State,
+    _: *mut mlua_sys::lua_Debug,
+) {
+    let terminated = TERMINATED.with_borrow(|terminated| {
+        terminated
+            .as_ref()
+            .is_some_and(|terminated| terminated.load(Ordering::Relaxed))
+    });
+
+    if terminated {
+        // Keep the hook installed, so that `pcall` can't swallow the termination
+        mlua_sys::luaL_error(state, b"Worker has been terminated\0".as_ptr().cast());
+    }
+}
+
+/// Installs `terminate_hook`. Must be called from the worker's thread,
+/// as setting hooks of a running state from other threads is a data race
+unsafe extern "C-unwind" fn install_terminate_hook(state: *mut mlua_sys::lua_State) -> c_int {
+    mlua_sys::lua_sethook(
+        state,
+        Some(terminate_hook),
+        mlua_sys::LUA_MASKCOUNT,
+        TERMINATE_CHECK_INTERVAL,
+    );
+    0
+}
+
+struct Shared {
+    terminated: Arc<AtomicBool>,
+    exit_reason: Mutex<Option<ExitReason>>,
+}
+
+impl Shared {
+    fn exit_reason(&self) -> Option<ExitReason> {
+        *self.exit_reason.lock().unwrap()
+    }
+}
+
+pub(super) fn format_error(err: LuaValue) -> String {
+    match err {
+        LuaValue::Error(err) => LuaErrorWrapper(err).to_string(),
+        value => value.to_string().unwrap_or_else(|_| format!("{:?}", value)),
+    }
+}
+
+#[derive(Clone)]
+pub enum WorkerCode {
+    String(String),
+    File(PathBuf),
+}
+
+/// Settings inherited from the state that starts a worker
+#[derive(Clone)]
+pub struct WorkerEnv {
+    package_path: String,
+    package_cpath: String,
+    pub(super) bytecode_cache: Option<BytecodeCache>,
+}
+
+impl WorkerEnv {
+    pub fn inherit(lua: &Lua) -> LuaResult<Self> {
+        let package = lua.globals().get::<_, LuaTable>("package")?;
+        Ok(Self {
+            package_path: package.get("path")?,
+            package_cpath: package.get("cpath")?,
+            bytecode_cache: lua.app_data_ref::<BytecodeCache>().map(|cache| *cache),
+        })
+    }
+
+    pub(super) fn apply(&self, lua: &Lua) -> LuaResult<()> {
+        let package = lua.globals().get::<_, LuaTable>("package")?;
+        package.set("path", self.package_path.as_str())?;
+        package.set("cpath", self.package_cpath.as_str())?;
+
+        if let Some(bytecode_cache) = self.bytecode_cache {
+            lua.set_app_data(bytecode_cache);
+        }
+
+        Ok(())
+    }
+}
+
+struct Startup {
+    code: WorkerCode,
+    name: String,
+    env: WorkerEnv,
+    args: Vec<WorkerData>,
+}
+
 pub struct Worker {
-    dead: Arc<AtomicBool>,
+    name: String,
+    shared: Arc<Shared>,
     sender: Sender<WorkerData>,
     receiver: Receiver<WorkerEvent>,
+    /// Data received by a `join` that has timed out
+    pending: Vec<WorkerData>,
 }
 
 impl Worker {
     pub fn start(
-        code: String,
+        code: WorkerCode,
         name: Option<String>,
         channel_size: Option<usize>,
+        env: WorkerEnv,
+        args: Vec<WorkerData>,
     ) -> Result<Self, Error> {
+        let name = name.unwrap_or_else(|| String::from("worker"));
+
         // worker -> main
         let (tx_, rx) = async_channel::bounded(channel_size.unwrap_or(32));
         // main -> worker
         let (tx, rx_) = async_channel::bounded(channel_size.unwrap_or(32));
 
-        let dead = Arc::new(AtomicBool::new(false));
-        let dead_ref = dead.clone();
-        std::thread::spawn(move || {
-            let lua = unsafe { Lua::new_with_stock_allocator() };
-            lua.load_from_std_lib(LuaStdLib::ALL).unwrap();
-
-            Self::setup_env(&lua, tx_.clone(), rx_).unwrap();
-
-            match lua
-                .load(code)
-                .set_name(name.as_deref().unwrap_or("worker"))
-                .exec()
-            {
-                Ok(_) => tx_.send_blocking(WorkerEvent::Done),
-                Err(err) => tx_.send_blocking(WorkerEvent::Error(err)),
-            }
-            .unwrap();
-
-            dead_ref.store(true, std::sync::atomic::Ordering::Relaxed);
+        let shared = Arc::new(Shared {
+            terminated: Arc::new(AtomicBool::new(false)),
+            exit_reason: Mutex::new(None),
         });
 
+        let startup = Startup {
+            code,
+            name: name.clone(),
+            env,
+            args,
+        };
+        let thread_shared = shared.clone();
+        std::thread::Builder::new()
+            .name(name.clone())
+            .spawn(move || {
+                let (reason, event) = Self::run(startup, &thread_shared, tx_.clone(), rx_);
+                *thread_shared.exit_reason.lock().unwrap() = Some(reason);
+
+                // Channel is closed if the worker has been terminated
+                let _ = tx_.send_blocking(event);
+            })?;
+
         Ok(Self {
-            dead,
+            name,
+            shared,
             sender: tx,
             receiver: rx,
+            pending: Vec::new(),
         })
     }
 
+    fn run(
+        startup: Startup,
+        shared: &Shared,
+        sender: Sender<WorkerEvent>,
+        receiver: Receiver<WorkerData>,
+    ) -> (ExitReason, WorkerEvent) {
+        let lua = unsafe { Lua::new_with_stock_allocator() };
+
+        let name = startup.name.clone();
+        TERMINATED.set(Some(shared.terminated.clone()));
+        let result = Self::exec(&lua, startup, shared, sender, receiver);
+
+        // The hook has to be removed, as finalizers run on close must not fail
+        lua.remove_hook();
+        TERMINATED.set(None);
+
+        if shared.terminated.load(Ordering::Relaxed) {
+            return (ExitReason::Terminated, WorkerEvent::Done);
+        }
+
+        match result {
+            Ok(Ok(())) => (ExitReason::Finished, WorkerEvent::Done),
+            Ok(Err(message)) | Err(message) => (
+                ExitReason::Failed,
+                WorkerEvent::Error(LuaError::RuntimeError(format!(
+                    "Worker `{name}` failed:\n{message}"
+                ))),
+            ),
+        }
+    }
+
+    /// Compiles the worker's code, using the bytecode cache for files if it's enabled
+    pub(super) fn load<'lua>(
+        lua: &'lua Lua,
+        code: WorkerCode,
+        name: &str,
+        bytecode_cache: Option<BytecodeCache>,
+    ) -> Result<LuaFunction<'lua>, String> {
+        let (path, source) = match code {
+            WorkerCode::String(code) => {
+                return lua
+                    .load(code)
+                    .set_name(name)
+                    .into_function()
+                    .map_err(|err| LuaErrorWrapper(err).to_string())
+            }
+            WorkerCode::File(path) => {
+                let source = fs::read_to_string(&path)
+                    .map_err(|err| format!("Failed to read `{}`: {err}", path.display()))?;
+                (path, source)
+            }
+        };
+
+        if let Some(bytecode_cache) = bytecode_cache {
+            bytecode_cache
+                .load(lua, &path, source, name)
+                .map_err(|err| match err {
+                    crate::error::Error::Lua(err) => LuaErrorWrapper(err).to_string(),
+                    err => format!("Failed to load `{}`: {err:?}", path.display()),
+                })
+        } else {
+            lua.load(source)
+                .set_name(name)
+                .into_function()
+                .map_err(|err| LuaErrorWrapper(err).to_string())
+        }
+    }
+
+    /// Runs the worker's code, returning its error message with a traceback on failure
+    fn exec(
+        lua: &Lua,
+        startup: Startup,
+        shared: &Shared,
+        sender: Sender<WorkerEvent>,
+        receiver: Receiver<WorkerData>,
+    ) -> Result<Result<(), String>, String> {
+        let setup = || -> LuaResult<(LuaFunction, LuaFunction)> {
+            lua.load_from_std_lib(LuaStdLib::ALL)?;
+
+            // Hooks aren't called from compiled traces,
+            // so `terminate` can only interrupt interpreted code
+            lua.globals()
+                .get::<_, LuaTable>("jit")?
+                .get::<_, LuaFunction>("off")?
+                .call::<_, ()>(())?;
+
+            let worker_table = Self::setup_env(lua)?;
+            worker_table.set("sender", lua.create_any_userdata(sender)?)?;
+            worker_table.set("receiver", lua.create_any_userdata(receiver)?)?;
+            startup.env.apply(lua)?;
+
+            let globals = lua.globals();
+            let xpcall = globals.get::<_, LuaFunction>("xpcall")?;
+            let traceback = globals
+                .get::<_, LuaTable>("debug")?
+                .get::<_, LuaFunction>("traceback")?;
+
+            Ok((xpcall, traceback))
+        };
+        let (xpcall, traceback) = setup().map_err(|err| LuaErrorWrapper(err).to_string())?;
+
+        let func = Self::load(lua, startup.code, &startup.name, startup.env.bytecode_cache)?;
+
+        unsafe { lua.create_c_function(install_terminate_hook) }
+            .and_then(|func| func.call::<_, ()>(()))
+            .map_err(|err| LuaErrorWrapper(err).to_string())?;
+
+        // Could have been terminated while loading the code
+        if shared.terminated.load(Ordering::Relaxed) {
+            return Ok(Ok(()));
+        }
+
+        let args = startup.args.into_iter().collect::<Variadic<_>>();
+        let (ok, err) = xpcall
+            .call::<_, (bool, LuaValue)>((func, traceback, args))
+            .map_err(|err| LuaErrorWrapper(err).to_string())?;
+
+        Ok(if ok { Ok(()) } else { Err(format_error(err)) })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn dead(&self) -> bool {
-        self.dead.load(Ordering::Relaxed)
+        self.shared.exit_reason().is_some()
+    }
+
+    pub fn exit_reason(&self) -> Option<ExitReason> {
+        self.shared.exit_reason()
+    }
+
+    /// Requests termination. The worker checks for it every `TERMINATE_CHECK_INTERVAL`
+    /// instructions and when it waits on any of its channels. Worker states run with the
+    /// JIT compiler turned off for this, but a long call into native code, e.g. a blocking read,
+    /// still has to return before the worker stops
+    pub fn terminate(&self) {
+        self.shared.terminated.store(true, Ordering::Relaxed);
+
+        // Wake up the worker if it's blocked on a channel.
+        // Data that has already been sent can still be received
+        self.sender.close();
+        self.receiver.close();
+    }
+
+    /// Collects data sent by the worker until it exits.
+    /// Returns `None` if `timeout` has elapsed, keeping collected data for the next call
+    pub async fn join(&mut self, timeout: Option<Duration>) -> LuaResult<Option<Vec<WorkerData>>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let event = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    match ::gtk::glib::future_with_timeout(timeout, self.receiver.recv()).await {
+                        Ok(event) => event,
+                        Err(_) => return Ok(None),
+                    }
+                }
+                None => self.receiver.recv().await,
+            };
+
+            if let Some(result) = self.handle_join_event(event) {
+                return result;
+            }
+        }
+    }
+
+    /// A blocking version of `join`
+    pub fn join_blocking(
+        &mut self,
+        timeout: Option<Duration>,
+    ) -> LuaResult<Option<Vec<WorkerData>>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        loop {
+            let event = match deadline {
+                Some(deadline) => {
+                    // Waits on a private context, so it works on any thread,
+                    // including the main one while its loop is blocked by this call
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    let event = ::gtk::glib::MainContext::new().block_on(
+                        ::gtk::glib::future_with_timeout(timeout, self.receiver.recv()),
+                    );
+
+                    match event {
+                        Ok(event) => event,
+                        Err(_) => return Ok(None),
+                    }
+                }
+                None => self.receiver.recv_blocking(),
+            };
+
+            if let Some(result) = self.handle_join_event(event) {
+                return result;
+            }
+        }
+    }
+
+    fn handle_join_event(
+        &mut self,
+        event: Result<WorkerEvent, RecvError>,
+    ) -> Option<LuaResult<Option<Vec<WorkerData>>>> {
+        match event {
+            Ok(WorkerEvent::UserData(data)) => {
+                self.pending.push(data);
+                None
+            }
+            Ok(WorkerEvent::Error(err)) => Some(Err(err)),
+            // Closed channel means that the worker has exited or has been terminated
+            Ok(WorkerEvent::Done) | Err(RecvError) => {
+                Some(Ok(Some(std::mem::take(&mut self.pending))))
+            }
+        }
     }
 
     pub fn sender(&mut self) -> Sender<WorkerData> {
@@ -217,30 +654,18 @@ impl Worker {
         Ok(())
     }
 
-    fn setup_env(
-        lua: &Lua,
-        sender: Sender<WorkerEvent>,
-        receiver: Receiver<WorkerData>,
-    ) -> LuaResult<()> {
+    /// Registers the `crabshell` global with thread-safe APIs only, returning its `worker` table
+    pub(super) fn setup_env(lua: &Lua) -> LuaResult<LuaTable> {
         let globals = lua.globals();
         let crabshell_table = lua.create_table()?;
-        gdk::push_api(lua, &crabshell_table)?;
-        gio::push_api(lua, &crabshell_table)?;
-        glib::push_api(lua, &crabshell_table)?;
-        gtk::push_api(lua, &crabshell_table)?;
-        hyprland::push_api(lua, &crabshell_table)?;
-        pulseaudio::push_api(lua, &crabshell_table)?;
-        sysinfo::push_api(lua, &crabshell_table)?;
-        utf8::push_api(lua, &crabshell_table)?;
-        utils::push_api(lua, &crabshell_table)?;
+        luaapi::push_thread_safe_api(lua, &crabshell_table)?;
 
         let worker_table = lua.create_table()?;
         Self::add_channels_api(lua, &worker_table)?;
-        worker_table.set("sender", lua.create_any_userdata(sender)?)?;
-        worker_table.set("receiver", lua.create_any_userdata(receiver)?)?;
-        crabshell_table.set("worker", worker_table)?;
+        shared::push_api(lua, &worker_table)?;
+        crabshell_table.set("worker", worker_table.clone())?;
         globals.set("crabshell", crabshell_table)?;
 
-        Ok(())
+        Ok(worker_table)
     }
 }
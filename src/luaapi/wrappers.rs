@@ -2,7 +2,11 @@ use gtk::{
     cairo::Context,
     gdk::{ModifierType, RGBA},
     gio::{ApplicationFlags, SubprocessFlags},
-    glib::GString,
+    glib::{
+        self, BindingFlags, BoxedAnyObject, EnumClass, EnumValue, FlagsClass, FlagsValue, GString,
+        Value, Variant, VariantTy,
+    },
+    prelude::*,
     EventControllerScrollFlags,
 };
 use mlua::prelude::*;
@@ -74,6 +78,360 @@ impl<'lua> IntoLua<'lua> for ContextWrapper {
     }
 }
 
+/// Converts a value to userdata, which has methods if `T` is registered with `LuaApi`
+pub struct UserDataWrapper<T>(pub T);
+impl<'lua, T: 'static> IntoLua<'lua> for UserDataWrapper<T> {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        Ok(LuaValue::UserData(lua.create_any_userdata(self.0)?))
+    }
+}
+
+/// Lua value that doesn't borrow the Lua state, so that it can be stored in GObjects
+#[derive(Clone)]
+pub enum OwnedValue {
+    Nil,
+    Boolean(bool),
+    LightUserData(LuaLightUserData),
+    Integer(LuaInteger),
+    Number(LuaNumber),
+    String(LuaOwnedString),
+    Table(LuaOwnedTable),
+    Function(LuaOwnedFunction),
+    Thread(LuaOwnedThread),
+    UserData(LuaOwnedAnyUserData),
+    Error(LuaError),
+}
+
+impl<'lua> FromLua<'lua> for OwnedValue {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        Ok(match value {
+            LuaValue::Nil => Self::Nil,
+            LuaValue::Boolean(value) => Self::Boolean(value),
+            LuaValue::LightUserData(value) => Self::LightUserData(value),
+            LuaValue::Integer(value) => Self::Integer(value),
+            LuaValue::Number(value) => Self::Number(value),
+            LuaValue::String(value) => Self::String(value.into_owned()),
+            LuaValue::Table(value) => Self::Table(value.into_owned()),
+            LuaValue::Function(value) => Self::Function(value.into_owned()),
+            LuaValue::Thread(value) => Self::Thread(value.into_owned()),
+            LuaValue::UserData(value) => Self::UserData(value.into_owned()),
+            LuaValue::Error(err) => Self::Error(err),
+        })
+    }
+}
+
+impl<'lua> IntoLua<'lua> for OwnedValue {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        match self {
+            Self::Nil => Ok(LuaValue::Nil),
+            Self::Boolean(value) => Ok(LuaValue::Boolean(value)),
+            Self::LightUserData(value) => Ok(LuaValue::LightUserData(value)),
+            Self::Integer(value) => Ok(LuaValue::Integer(value)),
+            Self::Number(value) => Ok(LuaValue::Number(value)),
+            Self::String(value) => value.into_lua(lua),
+            Self::Table(value) => value.into_lua(lua),
+            Self::Function(value) => value.into_lua(lua),
+            Self::Thread(value) => value.into_lua(lua),
+            Self::UserData(value) => value.into_lua(lua),
+            Self::Error(err) => Ok(LuaValue::Error(err)),
+        }
+    }
+}
+
+/// Wraps a Lua value into an item of a `ListModel`
+pub fn model_item(value: OwnedValue) -> BoxedAnyObject {
+    BoxedAnyObject::new(value)
+}
+
+/// Converts an item of a `ListModel` back to its Lua value.
+/// Items that haven't been created from Lua values are converted to nil
+pub struct ModelItemWrapper(pub Option<glib::Object>);
+impl<'lua> IntoLua<'lua> for ModelItemWrapper {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        let value = self
+            .0
+            .as_ref()
+            .and_then(|item| item.downcast_ref::<BoxedAnyObject>())
+            .and_then(|item| {
+                item.try_borrow::<OwnedValue>()
+                    .ok()
+                    .map(|value| value.clone())
+            });
+
+        value.unwrap_or(OwnedValue::Nil).into_lua(lua)
+    }
+}
+
+/// Converts between variants and booleans, integers (`x`), numbers (`d`) and strings (`s`).
+/// Variants of other types are converted to nil
+pub struct VariantWrapper(pub Variant);
+impl<'lua> FromLua<'lua> for VariantWrapper {
+    fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
+        let variant = match value {
+            LuaValue::Boolean(value) => value.to_variant(),
+            LuaValue::Integer(value) => value.to_variant(),
+            LuaValue::Number(value) => value.to_variant(),
+            LuaValue::String(value) => value.to_str()?.to_variant(),
+            _ => {
+                return Err(LuaError::FromLuaConversionError {
+                    from: value.type_name(),
+                    to: "Variant",
+                    message: Some(String::from("expected boolean, number or string")),
+                })
+            }
+        };
+
+        Ok(Self(variant))
+    }
+}
+
+impl<'lua> IntoLua<'lua> for VariantWrapper {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        let variant = self.0;
+        if let Some(value) = variant.get::<bool>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<u8>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<i16>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<u16>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<i32>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<i64>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<u32>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<u64>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.get::<f64>() {
+            value.into_lua(lua)
+        } else if let Some(value) = variant.str() {
+            value.into_lua(lua)
+        } else {
+            Ok(LuaValue::Nil)
+        }
+    }
+}
+
+/// Converts a Lua value to a variant of type `type_`, unlike `VariantWrapper`, which guesses
+/// the type. Integral doubles come as integers from LuaJIT, so they're accepted for `d` too
+pub fn variant_from_lua(value: &LuaValue, type_: &VariantTy) -> LuaResult<Variant> {
+    let integer = value.as_integer();
+    let converted = match type_.as_str() {
+        "b" => value.as_boolean().map(|value| value.to_variant()),
+        "y" => integer
+            .and_then(|value| u8::try_from(value).ok())
+            .map(|value| value.to_variant()),
+        "n" => integer
+            .and_then(|value| i16::try_from(value).ok())
+            .map(|value| value.to_variant()),
+        "q" => integer
+            .and_then(|value| u16::try_from(value).ok())
+            .map(|value| value.to_variant()),
+        "i" => integer
+            .and_then(|value| i32::try_from(value).ok())
+            .map(|value| value.to_variant()),
+        "u" => integer
+            .and_then(|value| u32::try_from(value).ok())
+            .map(|value| value.to_variant()),
+        "x" => integer.map(|value| value.to_variant()),
+        "t" => integer
+            .and_then(|value| u64::try_from(value).ok())
+            .map(|value| value.to_variant()),
+        "d" => value
+            .as_number()
+            .or(integer.map(|value| value as f64))
+            .map(|value| value.to_variant()),
+        "s" => match value {
+            LuaValue::String(value) => Some(value.to_str()?.to_variant()),
+            _ => None,
+        },
+        _ => {
+            return Err(LuaError::RuntimeError(format!(
+                "Variant type `{type_}` is not supported"
+            )))
+        }
+    };
+
+    converted.ok_or_else(|| {
+        LuaError::RuntimeError(format!(
+            "Expected a value of variant type `{type_}`, got {}",
+            match value {
+                LuaValue::Integer(value) => value.to_string(),
+                LuaValue::Number(value) => value.to_string(),
+                value => value.type_name().to_owned(),
+            }
+        ))
+    })
+}
+
+/// Converts a `Value` to a plain Lua value. String arrays are converted to tables, enums to
+/// nicks, flags to tables of nicks and objects to `Widget` if possible.
+/// Values of other types are kept as `Value` userdata
+pub struct ValueWrapper(pub Value);
+impl<'lua> IntoLua<'lua> for ValueWrapper {
+    fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
+        let value = self.0;
+        let type_ = value.type_();
+
+        match type_ {
+            glib::Type::BOOL => value.get::<bool>().into_lua_err()?.into_lua(lua),
+            glib::Type::I8 => value.get::<i8>().into_lua_err()?.into_lua(lua),
+            glib::Type::U8 => value.get::<u8>().into_lua_err()?.into_lua(lua),
+            glib::Type::I32 => value.get::<i32>().into_lua_err()?.into_lua(lua),
+            glib::Type::U32 => value.get::<u32>().into_lua_err()?.into_lua(lua),
+            glib::Type::I_LONG => value.get::<glib::ILong>().into_lua_err()?.0.into_lua(lua),
+            glib::Type::U_LONG => value.get::<glib::ULong>().into_lua_err()?.0.into_lua(lua),
+            glib::Type::I64 => value.get::<i64>().into_lua_err()?.into_lua(lua),
+            glib::Type::U64 => value.get::<u64>().into_lua_err()?.into_lua(lua),
+            glib::Type::F32 => value.get::<f32>().into_lua_err()?.into_lua(lua),
+            glib::Type::F64 => value.get::<f64>().into_lua_err()?.into_lua(lua),
+            glib::Type::STRING => value.get::<Option<String>>().into_lua_err()?.into_lua(lua),
+            _ if type_ == Vec::<String>::static_type() => {
+                value.get::<Vec<String>>().into_lua_err()?.into_lua(lua)
+            }
+            _ if type_.is_a(glib::Type::ENUM) => match EnumValue::from_value(&value) {
+                Some((_, enum_value)) => enum_value.nick().replace('-', "_").into_lua(lua),
+                None => Ok(LuaValue::Nil),
+            },
+            _ if type_.is_a(glib::Type::FLAGS) => {
+                let table = lua.create_table()?;
+                if let Some((_, flags)) = FlagsValue::from_value(&value) {
+                    for flag in flags {
+                        table.set(flag.nick().replace('-', "_"), true)?;
+                    }
+                }
+                Ok(LuaValue::Table(table))
+            }
+            _ if type_.is_a(glib::Type::OBJECT) => {
+                match value.get::<Option<glib::Object>>().into_lua_err()? {
+                    Some(object) => match object.downcast::<gtk::Widget>() {
+                        Ok(widget) => lua.create_any_userdata(widget)?.into_lua(lua),
+                        Err(object) => lua.create_any_userdata(object)?.into_lua(lua),
+                    },
+                    None => Ok(LuaValue::Nil),
+                }
+            }
+            _ => lua.create_any_userdata(value)?.into_lua(lua),
+        }
+    }
+}
+
+/// Gets the object of userdata created from any object type, through its `as_object` method
+pub fn object_from_userdata(ud: &LuaAnyUserData) -> Option<glib::Object> {
+    if let Ok(object) = ud.borrow::<glib::Object>() {
+        return Some(object.clone());
+    }
+
+    let object = ud.call_method::<_, LuaAnyUserData>("as_object", ()).ok()?;
+    let object = object.borrow::<glib::Object>().ok()?;
+    Some(object.clone())
+}
+
+/// Converts a Lua value to a `Value` of type `type_`. Enums are accepted as integers or nicks,
+/// flags as integers or tables of nicks. `Value` userdata of a matching type is passed through
+pub fn value_from_lua(value: &LuaValue, type_: glib::Type) -> LuaResult<Value> {
+    if let LuaValue::UserData(ud) = value {
+        if let Ok(value) = ud.borrow::<Value>() {
+            if value.type_().is_a(type_) {
+                return Ok(value.clone());
+            }
+        }
+    }
+
+    let conversion_error = || LuaError::FromLuaConversionError {
+        from: value.type_name(),
+        to: "Value",
+        message: Some(format!("expected a value of type `{}`", type_.name())),
+    };
+
+    let integer = value.as_integer();
+    let number = value.as_number().or(integer.map(|value| value as f64));
+
+    // `c_long` is `i64` on some platforms only
+    #[allow(clippy::useless_conversion)]
+    let converted = match type_ {
+        glib::Type::BOOL => value.as_boolean().map(|value| value.to_value()),
+        glib::Type::I8 => integer
+            .and_then(|value| i8::try_from(value).ok())
+            .map(|value| value.to_value()),
+        glib::Type::U8 => integer
+            .and_then(|value| u8::try_from(value).ok())
+            .map(|value| value.to_value()),
+        glib::Type::I32 => integer
+            .and_then(|value| i32::try_from(value).ok())
+            .map(|value| value.to_value()),
+        glib::Type::U32 => integer
+            .and_then(|value| u32::try_from(value).ok())
+            .map(|value| value.to_value()),
+        glib::Type::I_LONG => integer
+            .and_then(|value| value.try_into().ok())
+            .map(|value| glib::ILong(value).to_value()),
+        glib::Type::U_LONG => integer
+            .and_then(|value| value.try_into().ok())
+            .map(|value| glib::ULong(value).to_value()),
+        glib::Type::I64 => integer.map(|value| value.to_value()),
+        glib::Type::U64 => integer
+            .and_then(|value| u64::try_from(value).ok())
+            .map(|value| value.to_value()),
+        glib::Type::F32 => number.map(|value| (value as f32).to_value()),
+        glib::Type::F64 => number.map(|value| value.to_value()),
+        glib::Type::STRING => match value {
+            LuaValue::Nil => Some(None::<String>.to_value()),
+            LuaValue::String(value) => Some(value.to_str()?.to_value()),
+            _ => None,
+        },
+        _ if type_ == Vec::<String>::static_type() => match value {
+            LuaValue::Table(table) => Some(
+                table
+                    .clone()
+                    .sequence_values::<String>()
+                    .collect::<LuaResult<Vec<_>>>()?
+                    .to_value(),
+            ),
+            _ => None,
+        },
+        _ if type_.is_a(glib::Type::ENUM) => {
+            let class = EnumClass::with_type(type_).ok_or_else(conversion_error)?;
+            match value {
+                LuaValue::String(nick) => class.to_value_by_nick(&nick.to_str()?.replace('_', "-")),
+                _ => integer
+                    .and_then(|value| i32::try_from(value).ok())
+                    .and_then(|value| class.to_value(value)),
+            }
+        }
+        _ if type_.is_a(glib::Type::FLAGS) => {
+            let class = FlagsClass::with_type(type_).ok_or_else(conversion_error)?;
+            match value {
+                LuaValue::Table(table) => {
+                    let mut builder = class.builder();
+                    for pair in table.clone().pairs::<LuaString, bool>() {
+                        let (nick, set) = pair?;
+                        if set {
+                            builder = builder.set_by_nick(&nick.to_str()?.replace('_', "-"));
+                        }
+                    }
+                    builder.build()
+                }
+                _ => integer
+                    .and_then(|value| u32::try_from(value).ok())
+                    .and_then(|value| class.to_value(value)),
+            }
+        }
+        _ if type_.is_a(glib::Type::OBJECT) => match value {
+            LuaValue::Nil => Some(Value::from_type(type_)),
+            LuaValue::UserData(ud) => object_from_userdata(ud)
+                .filter(|object| object.type_().is_a(type_))
+                .map(|object| object.to_value()),
+            _ => None,
+        },
+        _ => None,
+    };
+
+    converted.ok_or_else(conversion_error)
+}
+
 pub struct RGBAWrapper(pub RGBA);
 impl<'lua> FromLua<'lua> for RGBAWrapper {
     fn from_lua(value: LuaValue<'lua>, _: &'lua Lua) -> LuaResult<Self> {
@@ -265,3 +623,53 @@ bitmask_from_lua_impl!(
         ALL
     ]
 );
+
+bitmask_from_lua_impl!(
+    BindingFlags,
+    DEFAULT,
+    [BIDIRECTIONAL, SYNC_CREATE, INVERT_BOOLEAN]
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ty(type_: &str) -> &VariantTy {
+        VariantTy::new(type_).unwrap()
+    }
+
+    #[test]
+    fn converts_variants_by_type() {
+        let variant = variant_from_lua(&LuaValue::Integer(5), ty("i")).unwrap();
+        assert_eq!(variant.get::<i32>(), Some(5));
+
+        let variant = variant_from_lua(&LuaValue::Integer(5), ty("u")).unwrap();
+        assert_eq!(variant.get::<u32>(), Some(5));
+
+        // LuaJIT passes `5.0` as an integer
+        let variant = variant_from_lua(&LuaValue::Integer(5), ty("d")).unwrap();
+        assert_eq!(variant.get::<f64>(), Some(5.0));
+
+        let variant = variant_from_lua(&LuaValue::Number(0.5), ty("d")).unwrap();
+        assert_eq!(variant.get::<f64>(), Some(0.5));
+
+        let variant = variant_from_lua(&LuaValue::Boolean(true), ty("b")).unwrap();
+        assert_eq!(variant.get::<bool>(), Some(true));
+
+        let lua = Lua::new();
+        let value = LuaValue::String(lua.create_string("on").unwrap());
+        let variant = variant_from_lua(&value, ty("s")).unwrap();
+        assert_eq!(variant.str(), Some("on"));
+    }
+
+    #[test]
+    fn rejects_mismatched_variants() {
+        assert!(variant_from_lua(&LuaValue::Number(0.5), ty("i")).is_err());
+        assert!(variant_from_lua(&LuaValue::Integer(-1), ty("u")).is_err());
+        assert!(variant_from_lua(&LuaValue::Integer(i64::MAX), ty("i")).is_err());
+        assert!(variant_from_lua(&LuaValue::Integer(1), ty("b")).is_err());
+        assert!(variant_from_lua(&LuaValue::Boolean(true), ty("d")).is_err());
+        assert!(variant_from_lua(&LuaValue::Nil, ty("s")).is_err());
+        assert!(variant_from_lua(&LuaValue::Integer(1), ty("as")).is_err());
+    }
+}
@@ -1,13 +1,8 @@
 use clap::Parser;
 use mlua::prelude::*;
-use std::{
-    collections::hash_map::DefaultHasher,
-    env, fs,
-    hash::{Hash, Hasher},
-    io::{Read, Write},
-    path::PathBuf,
-};
+use std::{env, fs, path::PathBuf};
 
+mod bytecode_cache;
 mod error;
 mod hyprland;
 mod luaapi;
@@ -17,6 +12,7 @@ mod traits;
 mod utils;
 
 use crate::{
+    bytecode_cache::BytecodeCache,
     error::{Error, LuaErrorWrapper},
     traits::LuaExt,
 };
@@ -42,19 +38,6 @@ struct Args {
     args: Vec<String>,
 }
 
-const CACHE_PATH: &str = "/tmp/crabshell";
-fn get_cache_path(config_path: &PathBuf) -> PathBuf {
-    let mut cache_path = PathBuf::from(CACHE_PATH);
-
-    let mut hasher = DefaultHasher::new();
-    config_path.hash(&mut hasher);
-    let config_path_hash = hasher.finish();
-    let config_path_hash = format!("{:016x}.bc", config_path_hash);
-    cache_path.push(config_path_hash);
-
-    cache_path
-}
-
 fn main() -> Result<(), Error> {
     let args = Args::parse();
     let config_path = args
@@ -87,7 +70,7 @@ fn main() -> Result<(), Error> {
     }
 
     if args.purge_cached_bytecode {
-        let cache_path = get_cache_path(&config_path);
+        let cache_path = BytecodeCache::cache_path(&config_path);
         if cache_path.is_file() {
             fs::remove_file(cache_path)?;
             return Ok(());
@@ -99,18 +82,18 @@ fn main() -> Result<(), Error> {
     let lua = unsafe { Lua::new_with_stock_allocator() };
     lua.load_from_std_lib(LuaStdLib::ALL)?;
 
+    let bytecode_cache = (!args.disable_bytecode_cache).then_some(BytecodeCache {
+        strip: args.strip_bytecode,
+    });
+
+    // Inherited by workers
+    if let Some(bytecode_cache) = bytecode_cache {
+        lua.set_app_data(bytecode_cache);
+    }
+
     let globals = lua.globals();
     let crabshell_table = lua.create_table()?;
-    luaapi::gdk::push_api(&lua, &crabshell_table)?;
-    luaapi::gio::push_api(&lua, &crabshell_table)?;
-    luaapi::glib::push_api(&lua, &crabshell_table)?;
-    luaapi::gtk::push_api(&lua, &crabshell_table)?;
-    luaapi::hyprland::push_api(&lua, &crabshell_table)?;
-    luaapi::pulseaudio::push_api(&lua, &crabshell_table)?;
-    luaapi::sysinfo::push_api(&lua, &crabshell_table)?;
-    luaapi::utf8::push_api(&lua, &crabshell_table)?;
-    luaapi::utils::push_api(&lua, &crabshell_table)?;
-    luaapi::worker::push_api(&lua, &crabshell_table)?;
+    luaapi::push_main_thread_api(&lua, &crabshell_table)?;
     globals.set("crabshell", crabshell_table)?;
 
     // Set current directory to the config path
@@ -130,57 +113,10 @@ fn main() -> Result<(), Error> {
     let config = fs::read_to_string(&config_path)?;
     let file_name = config_path.file_name().unwrap().to_str().unwrap();
 
-    let func = if args.disable_bytecode_cache {
-        lua.load(config).set_name(file_name).into_function()?
+    let func = if let Some(bytecode_cache) = bytecode_cache {
+        bytecode_cache.load(&lua, &config_path, config, file_name)?
     } else {
-        let cache_path = get_cache_path(&config_path);
-
-        let mut hasher = DefaultHasher::new();
-        config.hash(&mut hasher);
-        let new_config_hash = hasher.finish();
-
-        let load_config = || -> Result<LuaFunction, Error> {
-            // Compile config
-            let func = lua.load(config).set_name(file_name).into_function()?;
-
-            // Get bytecode
-            let bytecode = func.dump(args.strip_bytecode);
-
-            // Write bytecode to the cache
-            fs::create_dir_all(CACHE_PATH)?;
-
-            let cache_path = get_cache_path(&config_path);
-            let mut file = fs::File::create(cache_path)?;
-            file.write_all(&new_config_hash.to_le_bytes())?;
-            file.write_all(&bytecode)?;
-
-            Ok(func)
-        };
-
-        // Load bytecode
-        if cache_path.is_file() {
-            let mut cache_file = fs::File::open(cache_path)?;
-
-            let config_hash = {
-                let mut config_hash_data = [0u8; 8];
-                cache_file.read_exact(&mut config_hash_data)?;
-                u64::from_le_bytes(config_hash_data)
-            };
-
-            // Compare hash
-            if new_config_hash != config_hash {
-                // Hash mismatch: load config from file
-                load_config()?
-            } else {
-                // Load cached bytecode
-                let mut bytecode = Vec::new();
-                cache_file.read_to_end(&mut bytecode)?;
-                lua.load(bytecode).set_name(file_name).into_function()?
-            }
-        } else {
-            // Cache file does not exist: load config from file
-            load_config()?
-        }
+        lua.load(config).set_name(file_name).into_function()?
     };
 
     let lua_args = args
@@ -0,0 +1,338 @@
+use gtk::{
+    gio::{self, BusType, DBusCallFlags},
+    glib::{self, ControlFlow, SourceId, ToVariant},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::Duration,
+};
+
+use super::{
+    error::Error,
+    sysfs::{
+        self, parse_attribute, parse_optional_attribute, read_optional_attribute, write_attribute,
+    },
+};
+
+pub const BACKLIGHT_PATH: &str = "/sys/class/backlight";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BacklightInfo {
+    pub name: String,
+    /// `raw`, `platform` or `firmware`
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    /// 0 if it can't be read
+    pub brightness: u32,
+    /// 0 if it can't be read
+    pub max_brightness: u32,
+    /// Brightness reported by the hardware, which may differ from the requested one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_brightness: Option<u32>,
+    /// `brightness` to `max_brightness` ratio in percent
+    pub percent: f64,
+}
+
+/// Attributes that can't be read are skipped, e.g. `actual_brightness` often fails with `EIO`
+fn read_backlight(entry: &Path, name: String, buffer: &mut String) -> BacklightInfo {
+    let brightness =
+        parse_optional_attribute::<u32>(&entry.join("brightness"), buffer).unwrap_or(0);
+    let max_brightness =
+        parse_optional_attribute::<u32>(&entry.join("max_brightness"), buffer).unwrap_or(0);
+
+    BacklightInfo {
+        name,
+        typ: read_optional_attribute(&entry.join("type"), buffer),
+        brightness,
+        max_brightness,
+        actual_brightness: parse_optional_attribute(&entry.join("actual_brightness"), buffer),
+        percent: if max_brightness != 0 {
+            brightness as f64 * 100.0 / max_brightness as f64
+        } else {
+            0.0
+        },
+    }
+}
+
+pub fn get_backlights() -> Result<Vec<BacklightInfo>, Error> {
+    get_backlights_in(Path::new(BACKLIGHT_PATH))
+}
+
+/// Same as `get_backlights`, but reads devices from `root` instead of `/sys/class/backlight`
+pub fn get_backlights_in(root: &Path) -> Result<Vec<BacklightInfo>, Error> {
+    let mut backlights = Vec::new();
+    let mut buffer = String::with_capacity(16);
+    for entry in sysfs::read_dir(root)?
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+    {
+        let Ok(name) = entry.file_name().into_string() else {
+            continue;
+        };
+
+        backlights.push(read_backlight(&entry.path(), name, &mut buffer));
+    }
+
+    backlights.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(backlights)
+}
+
+pub fn get_backlight(name: &str) -> Result<BacklightInfo, Error> {
+    get_backlight_in(Path::new(BACKLIGHT_PATH), name)
+}
+
+/// Same as `get_backlight`, but reads the device from `root` instead of `/sys/class/backlight`
+pub fn get_backlight_in(root: &Path, name: &str) -> Result<BacklightInfo, Error> {
+    let entry = root.join(name);
+    if !entry.is_dir() {
+        return Err(Error::NoDevice(name.to_owned()));
+    }
+
+    Ok(read_backlight(
+        &entry,
+        name.to_owned(),
+        &mut String::with_capacity(16),
+    ))
+}
+
+/// Asks logind to change brightness, which doesn't require write access to sysfs
+async fn logind_set_brightness(name: &str, value: u32) -> Result<(), glib::Error> {
+    let connection = gio::bus_get_future(BusType::System).await?;
+    connection
+        .call_future(
+            Some("org.freedesktop.login1"),
+            "/org/freedesktop/login1/session/auto",
+            "org.freedesktop.login1.Session",
+            "SetBrightness",
+            Some(&("backlight", name, value).to_variant()),
+            None,
+            DBusCallFlags::NONE,
+            -1,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_brightness(name: &str, value: u32) -> Result<(), Error> {
+    set_brightness_in(Path::new(BACKLIGHT_PATH), name, value).await
+}
+
+/// Sets raw brightness, clamped to `max_brightness`.
+/// Uses logind when `root` is `/sys/class/backlight`, falling back to writing to sysfs directly
+pub async fn set_brightness_in(root: &Path, name: &str, value: u32) -> Result<(), Error> {
+    let entry = root.join(name);
+    if !entry.is_dir() {
+        return Err(Error::NoDevice(name.to_owned()));
+    }
+
+    // Reading tolerates a missing maximum, but here it would clamp brightness to 0
+    let max_brightness = parse_attribute::<u32>(
+        &entry.join("max_brightness"),
+        &mut String::with_capacity(16),
+    )?
+    .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+    let value = value.min(max_brightness);
+
+    if root == Path::new(BACKLIGHT_PATH) {
+        match logind_set_brightness(name, value).await {
+            Ok(()) => return Ok(()),
+            Err(err) => eprintln!("Failed to set brightness using logind: {err}"),
+        }
+    }
+
+    write_attribute(&entry.join("brightness"), &value.to_string())
+}
+
+type Handler = Box<dyn FnMut(&BacklightInfo)>;
+
+struct State {
+    root: PathBuf,
+    brightness: HashMap<String, u32>,
+    handlers: Vec<Handler>,
+}
+
+/// Reads backlights, returning `None` on errors
+fn read_backlights(state: &Rc<RefCell<State>>) -> Option<Vec<BacklightInfo>> {
+    let root = state.borrow().root.clone();
+    match get_backlights_in(&root) {
+        Ok(backlights) => Some(backlights),
+        Err(err) => {
+            eprintln!("Failed to read backlights: {:?}", err);
+            None
+        }
+    }
+}
+
+fn update(state: &Rc<RefCell<State>>) {
+    let Some(backlights) = read_backlights(state) else {
+        return;
+    };
+
+    let changed = {
+        let mut state = state.borrow_mut();
+        let changed: Vec<_> = backlights
+            .into_iter()
+            .filter(|info| state.brightness.get(&info.name) != Some(&info.brightness))
+            .collect();
+
+        for info in &changed {
+            state.brightness.insert(info.name.clone(), info.brightness);
+        }
+
+        changed
+    };
+
+    if changed.is_empty() {
+        return;
+    }
+
+    // Take handlers out to allow connecting new ones from inside of them
+    let mut handlers = std::mem::take(&mut state.borrow_mut().handlers);
+    for info in &changed {
+        for handler in handlers.iter_mut() {
+            handler(info);
+        }
+    }
+
+    let mut state = state.borrow_mut();
+    handlers.append(&mut state.handlers);
+    state.handlers = handlers;
+}
+
+/// Polls backlights, reporting brightness changes.
+/// The kernel doesn't notify about writes to sysfs, so polling is the only reliable option
+pub struct BacklightMonitor {
+    state: Rc<RefCell<State>>,
+    interval: Duration,
+    source: Option<SourceId>,
+}
+
+impl BacklightMonitor {
+    /// Returns a new stopped monitor
+    pub fn new(root: Option<PathBuf>, interval: Duration) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                root: root.unwrap_or_else(|| PathBuf::from(BACKLIGHT_PATH)),
+                brightness: HashMap::new(),
+                handlers: Vec::new(),
+            })),
+            interval,
+            source: None,
+        }
+    }
+
+    pub fn connect<F: FnMut(&BacklightInfo) + 'static>(&self, f: F) {
+        self.state.borrow_mut().handlers.push(Box::new(f));
+    }
+
+    pub fn running(&self) -> bool {
+        self.source.is_some()
+    }
+
+    pub fn start(&mut self) {
+        if self.running() {
+            return;
+        }
+
+        // Remember current values to compare against
+        if let Some(backlights) = read_backlights(&self.state) {
+            self.state.borrow_mut().brightness = backlights
+                .into_iter()
+                .map(|info| (info.name, info.brightness))
+                .collect();
+        }
+
+        let state = self.state.clone();
+        self.source = Some(glib::source::timeout_add_local(self.interval, move || {
+            update(&state);
+            ControlFlow::Continue
+        }));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(source) = self.source.take() {
+            source.remove();
+        }
+    }
+
+    /// Re-reads backlights immediately, emitting events for any changes
+    pub fn refresh(&self) {
+        update(&self.state);
+    }
+}
+
+impl Drop for BacklightMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_info::sysfs::fixture::Fixture;
+
+    #[test]
+    fn reads_backlights() {
+        let fixture = Fixture::new();
+        fixture
+            .write("intel_backlight/type", "raw\n")
+            .write("intel_backlight/brightness", "300\n")
+            .write("intel_backlight/max_brightness", "1200\n")
+            .write("intel_backlight/actual_brightness", "300\n");
+
+        let backlights = get_backlights_in(fixture.path()).unwrap();
+        assert_eq!(
+            backlights,
+            [BacklightInfo {
+                name: String::from("intel_backlight"),
+                typ: Some(String::from("raw")),
+                brightness: 300,
+                max_brightness: 1200,
+                actual_brightness: Some(300),
+                percent: 25.0,
+            }]
+        );
+        assert_eq!(
+            get_backlight_in(fixture.path(), "intel_backlight").unwrap(),
+            backlights[0]
+        );
+    }
+
+    #[test]
+    fn broken_attributes_dont_hide_backlights() {
+        let fixture = Fixture::new();
+        fixture
+            .write("amdgpu_bl0/brightness", "128\n")
+            .write("amdgpu_bl0/max_brightness", "255\n")
+            .unreadable("amdgpu_bl0/actual_brightness")
+            .write("acpi_video0/brightness", "garbage\n")
+            .write("acpi_video0/max_brightness", "10\n");
+
+        let backlights = get_backlights_in(fixture.path()).unwrap();
+        assert_eq!(backlights.len(), 2);
+        assert_eq!(backlights[0].name, "acpi_video0");
+        assert_eq!(backlights[0].brightness, 0);
+        assert_eq!(backlights[1].brightness, 128);
+        assert_eq!(backlights[1].actual_brightness, None);
+    }
+
+    #[test]
+    fn missing_backlights() {
+        let fixture = Fixture::new();
+        assert!(get_backlights_in(&fixture.path().join("missing"))
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            get_backlight_in(fixture.path(), "intel_backlight"),
+            Err(Error::NoDevice(_))
+        ));
+    }
+}
@@ -1,19 +1,63 @@
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    fs::{read_to_string, File},
-    io::{self, Read},
-    path::Path,
+    path::{Path, PathBuf},
+    str::FromStr,
     time::Duration,
 };
 
+use super::{
+    error::Error,
+    sysfs::{self, parse_optional_attribute, read_attribute, read_optional_attribute},
+};
+
+pub const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+/// Unit of `full`, `now` and `full_design` values.
+/// `current` is measured in the same unit per hour
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnergyUnit {
+    #[serde(rename = "uWh")]
+    MicroWattHours,
+    #[serde(rename = "uAh")]
+    MicroAmpHours,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct BatteryInfo {
-    pub capacity: i32,
-    pub full: f64,
-    pub now: f64,
-    pub current: f64,
-    pub remaining_time: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capacity_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unit: Option<EnergyUnit>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full_design: Option<f64>,
+    /// `full` to `full_design` ratio in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub now: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<f64>,
+    /// Power draw in watts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<f64>,
+    /// Voltage in µV
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voltage_now: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining_time: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycle_count: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub technology: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_name: Option<String>,
     pub status: String,
 }
 
@@ -24,40 +68,49 @@ pub struct Batteries {
     pub remaining_time: Duration,
 }
 
-fn read_to_string_buf<P: AsRef<Path>>(path: P, buffer: &mut String) -> io::Result<&mut String> {
-    let mut file = File::open(path)?;
-    buffer.clear();
-    file.read_to_string(buffer)?;
-    Ok(buffer)
-}
-
-pub fn is_on_ac() -> bool {
-    let power_supply_dir = Path::new("/sys/class/power_supply");
-    let power_supplies = power_supply_dir
-        .read_dir()
-        .expect("Failed to read /sys/class/power_supply/");
+/// Iterates over power supplies of the given type
+fn power_supplies<'a>(
+    root: &Path,
+    typ: &'a str,
+) -> Result<impl Iterator<Item = PathBuf> + 'a, Error> {
+    // No power supplies at all, e.g. on a desktop
+    let entries = sysfs::read_dir(root)?;
 
     let mut buffer = String::with_capacity(16);
-    for entry in power_supplies {
-        let entry = entry.expect("Failed to get power supply entry").path();
-
-        // Skip batteries
-        if read_to_string_buf(entry.join("type"), &mut buffer)
-            .map(|t| t != "Mains\n")
-            .unwrap_or(true)
-        {
-            continue;
+    Ok(entries.into_iter().flatten().filter_map(move |entry| {
+        let entry = entry.ok()?.path();
+
+        // Supplies whose type can't be read are skipped instead of failing the others
+        match read_attribute(&entry.join("type"), &mut buffer) {
+            Ok(Some(value)) if value == typ => Some(entry),
+            _ => None,
         }
+    }))
+}
+
+/// Reads an optional attribute of `entry`, see `sysfs::parse_optional_attribute`
+fn optional<T: FromStr>(entry: &Path, name: &str, buffer: &mut String) -> Option<T> {
+    parse_optional_attribute(&entry.join(name), buffer)
+}
+
+fn optional_string(entry: &Path, name: &str, buffer: &mut String) -> Option<String> {
+    read_optional_attribute(&entry.join(name), buffer)
+}
+
+pub fn is_on_ac() -> Result<bool, Error> {
+    is_on_ac_in(Path::new(POWER_SUPPLY_PATH))
+}
 
-        if read_to_string_buf(entry.join("online"), &mut buffer)
-            .map(|value| value == "1\n")
-            .unwrap_or(false)
-        {
-            return true;
+/// Same as `is_on_ac`, but reads power supplies from `root` instead of `/sys/class/power_supply`
+pub fn is_on_ac_in(root: &Path) -> Result<bool, Error> {
+    let mut buffer = String::with_capacity(16);
+    for entry in power_supplies(root, "Mains")? {
+        if optional::<i32>(&entry, "online", &mut buffer) == Some(1) {
+            return Ok(true);
         }
     }
 
-    false
+    Ok(false)
 }
 
 fn battery_time(on_ac: bool, full: f64, now: f64, current: f64) -> Duration {
@@ -74,95 +127,191 @@ fn battery_time(on_ac: bool, full: f64, now: f64, current: f64) -> Duration {
     }
 }
 
+/// `(full, now, full_design)`
+type Levels = (Option<f64>, Option<f64>, Option<f64>);
+
+/// Reads `{prefix}_full`, `{prefix}_now` and `{prefix}_full_design`
+fn read_levels(entry: &Path, prefix: &str, buffer: &mut String) -> Levels {
+    let full = optional(entry, &format!("{prefix}_full"), buffer);
+    let now = optional(entry, &format!("{prefix}_now"), buffer);
+    let full_design = optional(entry, &format!("{prefix}_full_design"), buffer);
+    (full, now, full_design)
+}
+
+fn read_battery(entry: &Path, on_ac: bool, buffer: &mut String) -> BatteryInfo {
+    // Batteries report either energy (µWh) or charge (µAh)
+    let (unit, (full, now, full_design)) = match read_levels(entry, "energy", buffer) {
+        (None, None, None) => match read_levels(entry, "charge", buffer) {
+            (None, None, None) => (None, (None, None, None)),
+            levels => (Some(EnergyUnit::MicroAmpHours), levels),
+        },
+        levels => (Some(EnergyUnit::MicroWattHours), levels),
+    };
+
+    let voltage_now = optional::<f64>(entry, "voltage_now", buffer);
+    let power_now = optional::<f64>(entry, "power_now", buffer).map(f64::abs);
+    let current_now = optional::<f64>(entry, "current_now", buffer).map(f64::abs);
+
+    // µW
+    let power = power_now.or_else(|| Some(current_now? * voltage_now? / 1_000_000.0));
+
+    // Rate of `now` change, in `unit` per hour
+    let current = match unit {
+        Some(EnergyUnit::MicroWattHours) => power,
+        Some(EnergyUnit::MicroAmpHours) => current_now.or_else(|| {
+            let voltage_now = voltage_now.filter(|&voltage| voltage > 0.0)?;
+            Some(power_now? * 1_000_000.0 / voltage_now)
+        }),
+        None => power_now.or(current_now),
+    };
+
+    // Some devices only report the charge level
+    let capacity = match optional::<i32>(entry, "capacity", buffer) {
+        Some(capacity) => Some(capacity),
+        None => match (full, now) {
+            (Some(full), Some(now)) if full > 0.0 => Some((now * 100.0 / full).round() as i32),
+            _ => None,
+        },
+    };
+
+    let health = match (full, full_design) {
+        (Some(full), Some(full_design)) if full_design > 0.0 => Some(full * 100.0 / full_design),
+        _ => None,
+    };
+
+    let status =
+        optional_string(entry, "status", buffer).unwrap_or_else(|| String::from("Unknown"));
+
+    let remaining_time = match (full, now, current) {
+        (Some(full), Some(now), Some(current)) => Some(battery_time(on_ac, full, now, current)),
+        _ => None,
+    };
+
+    BatteryInfo {
+        capacity,
+        capacity_level: optional_string(entry, "capacity_level", buffer),
+        unit,
+        full,
+        full_design,
+        health,
+        now,
+        current,
+        power: power.map(|power| power / 1_000_000.0),
+        voltage_now,
+        remaining_time,
+        cycle_count: optional(entry, "cycle_count", buffer),
+        technology: optional_string(entry, "technology", buffer),
+        manufacturer: optional_string(entry, "manufacturer", buffer),
+        model_name: optional_string(entry, "model_name", buffer),
+        status,
+    }
+}
+
+pub fn get_batteries() -> Result<Batteries, Error> {
+    get_batteries_in(Path::new(POWER_SUPPLY_PATH))
+}
+
 // https://github.com/elkowar/eww/blob/dc3129aee2806823bdad87785f7ef80651d5245c/crates/eww/src/config/system_stats.rs#L118
 // https://github.com/valpackett/systemstat/blob/cbd9c1638b792d1819479f0c2baa5840f65af727/src/platform/linux.rs#L584
-pub fn get_batteries() -> Batteries {
-    let mut batteries = HashMap::new();
-
-    let on_ac = is_on_ac();
+/// Same as `get_batteries`, but reads power supplies from `root` instead of `/sys/class/power_supply`
+pub fn get_batteries_in(root: &Path) -> Result<Batteries, Error> {
+    read_batteries_in(root, is_on_ac_in(root)?)
+}
 
-    let power_supply_dir = Path::new("/sys/class/power_supply");
-    let power_supplies = power_supply_dir
-        .read_dir()
-        .expect("Failed to read /sys/class/power_supply/");
+/// Same as `get_batteries_in`, for callers that have already checked `is_on_ac_in`
+pub(super) fn read_batteries_in(root: &Path, on_ac: bool) -> Result<Batteries, Error> {
+    let mut batteries = HashMap::new();
 
     let mut total_capacity = 0;
     let mut full_total = 0.0;
     let mut now_total = 0.0;
     let mut current_total = 0.0;
     let mut buffer = String::with_capacity(16);
-    for entry in power_supplies {
-        let entry = entry.expect("Failed to get power supply entry").path();
-
-        // Skip non-batteries
-        if read_to_string_buf(entry.join("type"), &mut buffer)
-            .map(|t| t != "Battery\n")
-            .unwrap_or(true)
-        {
+    for entry in power_supplies(root, "Battery")? {
+        let Some(name) = entry.file_name() else {
             continue;
-        }
-
-        let capacity = read_to_string_buf(entry.join("capacity"), &mut buffer)
-            .expect("Failed to read battery capacity")
-            .trim_end_matches('\n')
-            .parse::<i32>()
-            .expect("Failed to parse battery capacity");
-
-        let status = {
-            let mut str = read_to_string_buf(entry.join("status"), &mut buffer)
-                .expect("Failed to read battery status")
-                .to_owned();
-            str.truncate(str.trim_end_matches('\n').len());
-            str
         };
 
-        let full = read_to_string(entry.join("energy_full"))
-            .or_else(|_| read_to_string(entry.join("charge_full")))
-            .expect("Failed to read battery full charge")
-            .trim_end_matches('\n')
-            .parse::<f64>()
-            .expect("Failed to parse battery full charge");
-
-        let now = read_to_string(entry.join("energy_now"))
-            .or_else(|_| read_to_string(entry.join("charge_now")))
-            .expect("Failed to read battery current charge")
-            .trim_end_matches('\n')
-            .parse::<f64>()
-            .expect("Failed to parse battery current charge");
-
-        let current = read_to_string(entry.join("power_now"))
-            .or_else(|_| read_to_string(entry.join("current_now")))
-            .expect("Failed to read battery current")
-            .trim_end_matches('\n')
-            .parse::<f64>()
-            .expect("Failed to parse battery current");
-
-        total_capacity += capacity;
-        full_total += full;
-        now_total += now;
-        current_total += current;
-
-        let remaining_time = battery_time(on_ac, full, now, current);
-        batteries.insert(
-            entry
-                .file_name()
-                .expect("Failed to get battery name")
-                .to_string_lossy()
-                .to_string(),
-            BatteryInfo {
-                capacity,
-                remaining_time,
-                full,
-                now,
-                current,
-                status,
-            },
-        );
+        let info = read_battery(&entry, on_ac, &mut buffer);
+        total_capacity += info.capacity.unwrap_or(0);
+
+        // Only devices that report every value can be used to estimate the total time
+        if let (Some(full), Some(now), Some(current)) = (info.full, info.now, info.current) {
+            full_total += full;
+            now_total += now;
+            current_total += current;
+        }
+
+        batteries.insert(name.to_string_lossy().to_string(), info);
     }
 
-    Batteries {
+    Ok(Batteries {
         info: batteries,
         total_capacity,
         remaining_time: battery_time(on_ac, full_total, now_total, current_total),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_info::sysfs::fixture::Fixture;
+
+    fn laptop_battery(fixture: &Fixture) {
+        fixture
+            .write("BAT0/type", "Battery\n")
+            .write("BAT0/status", "Discharging\n")
+            .write("BAT0/capacity", "50\n")
+            .write("BAT0/energy_full", "40000000\n")
+            .write("BAT0/energy_now", "20000000\n")
+            .write("BAT0/power_now", "10000000\n");
+    }
+
+    #[test]
+    fn reads_battery() {
+        let fixture = Fixture::new();
+        laptop_battery(&fixture);
+        fixture
+            .write("AC/type", "Mains\n")
+            .write("AC/online", "0\n");
+
+        let batteries = get_batteries_in(fixture.path()).unwrap();
+        let info = &batteries.info["BAT0"];
+        assert_eq!(info.capacity, Some(50));
+        assert_eq!(info.unit, Some(EnergyUnit::MicroWattHours));
+        assert_eq!(info.power, Some(10.0));
+        assert_eq!(info.status, "Discharging");
+        assert_eq!(batteries.total_capacity, 50);
+        assert_eq!(batteries.remaining_time, Duration::from_secs(2 * 3600));
+        assert!(!is_on_ac_in(fixture.path()).unwrap());
+    }
+
+    #[test]
+    fn broken_supplies_dont_hide_others() {
+        let fixture = Fixture::new();
+        laptop_battery(&fixture);
+        fixture
+            .write("hid-mouse-battery/type", "Battery\n")
+            .write("hid-mouse-battery/capacity", "not a number\n")
+            .unreadable("hid-mouse-battery/status")
+            .unreadable("broken/type")
+            .write("AC/type", "Mains\n")
+            .write("AC/online", "yes\n");
+
+        let batteries = get_batteries_in(fixture.path()).unwrap();
+        assert_eq!(batteries.info["BAT0"].capacity, Some(50));
+
+        let mouse = &batteries.info["hid-mouse-battery"];
+        assert_eq!(mouse.capacity, None);
+        assert_eq!(mouse.status, "Unknown");
+        assert!(!batteries.info.contains_key("broken"));
+        assert!(!is_on_ac_in(fixture.path()).unwrap());
+    }
+
+    #[test]
+    fn no_power_supplies() {
+        let fixture = Fixture::new();
+        let batteries = get_batteries_in(&fixture.path().join("missing")).unwrap();
+        assert!(batteries.info.is_empty());
     }
 }
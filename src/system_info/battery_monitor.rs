@@ -0,0 +1,486 @@
+use gtk::glib::{self, ControlFlow, IOCondition, SourceId};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    path::PathBuf,
+    rc::Rc,
+    time::Duration,
+};
+
+use super::battery::{self, Batteries};
+
+/// Kernel uevent multicast group
+const UEVENT_GROUP: u32 = 1;
+
+/// Shorter polling intervals would keep the main loop busy re-reading sysfs
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatteryEvent {
+    /// AC adapter has been plugged or unplugged
+    AcChanged { online: bool },
+    /// Battery has been added or removed
+    BatteryChanged { name: String, present: bool },
+    StatusChanged {
+        name: String,
+        status: String,
+        previous: String,
+    },
+    /// Battery capacity went below (`falling`) or above a threshold
+    ThresholdCrossed {
+        name: String,
+        threshold: i32,
+        capacity: i32,
+        falling: bool,
+    },
+}
+
+struct Snapshot {
+    on_ac: bool,
+    statuses: HashMap<String, String>,
+    capacities: HashMap<String, i32>,
+}
+
+impl Snapshot {
+    fn new(on_ac: bool, batteries: &Batteries) -> Self {
+        Self {
+            on_ac,
+            statuses: batteries
+                .info
+                .iter()
+                .map(|(name, info)| (name.clone(), info.status.clone()))
+                .collect(),
+            capacities: batteries
+                .info
+                .iter()
+                .filter_map(|(name, info)| Some((name.clone(), info.capacity?)))
+                .collect(),
+        }
+    }
+}
+
+type Handler = Box<dyn FnMut(&BatteryEvent)>;
+
+struct State {
+    root: PathBuf,
+    thresholds: Vec<i32>,
+    snapshot: Option<Snapshot>,
+    handlers: Vec<Handler>,
+}
+
+impl State {
+    fn diff(&self, new: &Snapshot) -> Vec<BatteryEvent> {
+        let mut events = Vec::new();
+        let Some(old) = &self.snapshot else {
+            return events;
+        };
+
+        if old.on_ac != new.on_ac {
+            events.push(BatteryEvent::AcChanged { online: new.on_ac });
+        }
+
+        for name in old.statuses.keys() {
+            if !new.statuses.contains_key(name) {
+                events.push(BatteryEvent::BatteryChanged {
+                    name: name.clone(),
+                    present: false,
+                });
+            }
+        }
+
+        for (name, status) in &new.statuses {
+            match old.statuses.get(name) {
+                Some(previous) if previous != status => events.push(BatteryEvent::StatusChanged {
+                    name: name.clone(),
+                    status: status.clone(),
+                    previous: previous.clone(),
+                }),
+                Some(_) => {}
+                None => events.push(BatteryEvent::BatteryChanged {
+                    name: name.clone(),
+                    present: true,
+                }),
+            }
+        }
+
+        for (name, &capacity) in &new.capacities {
+            let Some(&previous) = old.capacities.get(name) else {
+                continue;
+            };
+
+            for &threshold in &self.thresholds {
+                if previous > threshold && capacity <= threshold {
+                    events.push(BatteryEvent::ThresholdCrossed {
+                        name: name.clone(),
+                        threshold,
+                        capacity,
+                        falling: true,
+                    });
+                } else if previous <= threshold && capacity > threshold {
+                    events.push(BatteryEvent::ThresholdCrossed {
+                        name: name.clone(),
+                        threshold,
+                        capacity,
+                        falling: false,
+                    });
+                }
+            }
+        }
+
+        events
+    }
+}
+
+/// Reads power supply state, returning `None` on errors
+fn read_snapshot(state: &Rc<RefCell<State>>) -> Option<Snapshot> {
+    let root = state.borrow().root.clone();
+    let result = battery::is_on_ac_in(&root).and_then(|on_ac| {
+        let batteries = battery::read_batteries_in(&root, on_ac)?;
+        Ok(Snapshot::new(on_ac, &batteries))
+    });
+
+    match result {
+        Ok(snapshot) => Some(snapshot),
+        Err(err) => {
+            eprintln!("Failed to read power supplies: {:?}", err);
+            None
+        }
+    }
+}
+
+fn update(state: &Rc<RefCell<State>>) {
+    let Some(snapshot) = read_snapshot(state) else {
+        return;
+    };
+
+    let events = {
+        let mut state = state.borrow_mut();
+        let events = state.diff(&snapshot);
+        state.snapshot = Some(snapshot);
+        events
+    };
+
+    if events.is_empty() {
+        return;
+    }
+
+    // Take handlers out to allow connecting new ones from inside of them
+    let mut handlers = std::mem::take(&mut state.borrow_mut().handlers);
+    for event in &events {
+        for handler in handlers.iter_mut() {
+            handler(event);
+        }
+    }
+
+    let mut state = state.borrow_mut();
+    handlers.append(&mut state.handlers);
+    state.handlers = handlers;
+}
+
+fn open_uevent_socket() -> io::Result<OwnedFd> {
+    unsafe {
+        let fd = libc::socket(
+            libc::AF_NETLINK,
+            libc::SOCK_DGRAM | libc::SOCK_NONBLOCK | libc::SOCK_CLOEXEC,
+            libc::NETLINK_KOBJECT_UEVENT,
+        );
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = OwnedFd::from_raw_fd(fd);
+
+        let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = UEVENT_GROUP;
+        if libc::bind(
+            fd.as_raw_fd(),
+            &addr as *const libc::sockaddr_nl as *const libc::sockaddr,
+            std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t,
+        ) < 0
+        {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(fd)
+    }
+}
+
+/// Drains the socket, returning whether any power supply uevents were received
+fn drain_uevents(fd: &OwnedFd) -> io::Result<bool> {
+    let mut buffer = [0u8; 4096];
+    let mut power_supply_changed = false;
+    loop {
+        let read = unsafe {
+            libc::recv(
+                fd.as_raw_fd(),
+                buffer.as_mut_ptr() as *mut libc::c_void,
+                buffer.len(),
+                0,
+            )
+        };
+
+        if read < 0 {
+            let err = io::Error::last_os_error();
+            return match err.kind() {
+                io::ErrorKind::WouldBlock => Ok(power_supply_changed),
+                io::ErrorKind::Interrupted => continue,
+                _ => Err(err),
+            };
+        }
+
+        // Message consists of NUL-separated `KEY=value` pairs
+        power_supply_changed |= buffer[..read as usize]
+            .split(|&byte| byte == 0)
+            .any(|field| field == b"SUBSYSTEM=power_supply");
+    }
+}
+
+fn poll(state: Rc<RefCell<State>>, interval: Duration) -> SourceId {
+    glib::source::timeout_add_local(interval, move || {
+        update(&state);
+        ControlFlow::Continue
+    })
+}
+
+/// Watches power supplies, reporting changes either on kernel uevents or periodically
+pub struct BatteryMonitor {
+    state: Rc<RefCell<State>>,
+    interval: Duration,
+    // Shared with the uevent source, which replaces itself with a poller if the socket breaks
+    source: Rc<RefCell<Option<SourceId>>>,
+    uevent: Rc<Cell<bool>>,
+}
+
+impl BatteryMonitor {
+    /// Returns a new stopped monitor.
+    /// `interval` is used for polling when uevents are unavailable, and is clamped to
+    /// `MIN_POLL_INTERVAL`
+    pub fn new(root: Option<PathBuf>, thresholds: Vec<i32>, interval: Duration) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                root: root.unwrap_or_else(|| PathBuf::from(battery::POWER_SUPPLY_PATH)),
+                thresholds,
+                snapshot: None,
+                handlers: Vec::new(),
+            })),
+            interval: interval.max(MIN_POLL_INTERVAL),
+            source: Rc::new(RefCell::new(None)),
+            uevent: Rc::new(Cell::new(false)),
+        }
+    }
+
+    pub fn connect<F: FnMut(&BatteryEvent) + 'static>(&self, f: F) {
+        self.state.borrow_mut().handlers.push(Box::new(f));
+    }
+
+    pub fn set_thresholds(&self, thresholds: Vec<i32>) {
+        self.state.borrow_mut().thresholds = thresholds;
+    }
+
+    pub fn running(&self) -> bool {
+        self.source.borrow().is_some()
+    }
+
+    /// Returns whether the monitor listens to kernel uevents instead of polling
+    pub fn uses_uevent(&self) -> bool {
+        self.uevent.get()
+    }
+
+    /// Starts monitoring. Falls back to polling if the uevent socket can't be opened or breaks
+    pub fn start(&mut self, force_polling: bool) {
+        if self.running() {
+            return;
+        }
+
+        // Take the initial snapshot to compare against
+        let snapshot = read_snapshot(&self.state);
+        self.state.borrow_mut().snapshot = snapshot;
+
+        let socket = if force_polling {
+            None
+        } else {
+            match open_uevent_socket() {
+                Ok(socket) => Some(socket),
+                Err(err) => {
+                    eprintln!("Failed to open uevent socket, falling back to polling: {err}");
+                    None
+                }
+            }
+        };
+
+        let state = self.state.clone();
+        self.uevent.set(socket.is_some());
+        let source = if let Some(socket) = socket {
+            let interval = self.interval;
+            let source = self.source.clone();
+            let uevent = self.uevent.clone();
+            glib::source::unix_fd_add_local(
+                socket.as_raw_fd(),
+                IOCondition::IN | IOCondition::ERR | IOCondition::HUP,
+                move |_, condition| {
+                    // The socket stays ready once it breaks, so stop watching it
+                    if condition.intersects(IOCondition::ERR | IOCondition::HUP) {
+                        eprintln!("uevent socket failed, falling back to polling");
+                        uevent.set(false);
+                        source.replace(Some(poll(state.clone(), interval)));
+                        update(&state);
+                        return ControlFlow::Break;
+                    }
+
+                    match drain_uevents(&socket) {
+                        Ok(true) => update(&state),
+                        Ok(false) => {}
+                        Err(err) => {
+                            // Events might have been dropped, so re-read everything
+                            eprintln!("Failed to read uevents: {err}");
+                            update(&state);
+                        }
+                    }
+
+                    ControlFlow::Continue
+                },
+            )
+        } else {
+            poll(state, self.interval)
+        };
+        self.source.replace(Some(source));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(source) = self.source.take() {
+            source.remove();
+        }
+    }
+
+    /// Re-reads power supplies immediately, emitting events for any changes
+    pub fn refresh(&self) {
+        update(&self.state);
+    }
+}
+
+impl Drop for BatteryMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn old_state(thresholds: Vec<i32>, on_ac: bool, batteries: &[(&str, &str, i32)]) -> State {
+        State {
+            root: PathBuf::new(),
+            thresholds,
+            snapshot: Some(snapshot(on_ac, batteries)),
+            handlers: Vec::new(),
+        }
+    }
+
+    fn snapshot(on_ac: bool, batteries: &[(&str, &str, i32)]) -> Snapshot {
+        Snapshot {
+            on_ac,
+            statuses: batteries
+                .iter()
+                .map(|&(name, status, _)| (name.to_owned(), status.to_owned()))
+                .collect(),
+            capacities: batteries
+                .iter()
+                .map(|&(name, _, capacity)| (name.to_owned(), capacity))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn first_snapshot_has_no_events() {
+        let state = State {
+            snapshot: None,
+            ..old_state(vec![20], false, &[])
+        };
+        assert!(state
+            .diff(&snapshot(true, &[("BAT0", "Charging", 50)]))
+            .is_empty());
+    }
+
+    #[test]
+    fn ac_changes() {
+        let state = old_state(vec![], false, &[("BAT0", "Discharging", 50)]);
+        assert_eq!(
+            state.diff(&snapshot(true, &[("BAT0", "Discharging", 50)])),
+            [BatteryEvent::AcChanged { online: true }]
+        );
+        assert!(state
+            .diff(&snapshot(false, &[("BAT0", "Discharging", 50)]))
+            .is_empty());
+    }
+
+    #[test]
+    fn status_changes() {
+        let state = old_state(vec![], false, &[("BAT0", "Discharging", 50)]);
+        assert_eq!(
+            state.diff(&snapshot(false, &[("BAT0", "Charging", 50)])),
+            [BatteryEvent::StatusChanged {
+                name: String::from("BAT0"),
+                status: String::from("Charging"),
+                previous: String::from("Discharging"),
+            }]
+        );
+    }
+
+    #[test]
+    fn batteries_added_and_removed() {
+        let state = old_state(vec![], false, &[("BAT0", "Discharging", 50)]);
+        assert_eq!(
+            state.diff(&snapshot(false, &[("BAT1", "Discharging", 50)])),
+            [
+                BatteryEvent::BatteryChanged {
+                    name: String::from("BAT0"),
+                    present: false,
+                },
+                BatteryEvent::BatteryChanged {
+                    name: String::from("BAT1"),
+                    present: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn thresholds_crossed() {
+        let state = old_state(vec![10, 20], false, &[("BAT0", "Discharging", 21)]);
+
+        // Reaching a threshold counts as crossing it when falling
+        assert_eq!(
+            state.diff(&snapshot(false, &[("BAT0", "Discharging", 20)])),
+            [BatteryEvent::ThresholdCrossed {
+                name: String::from("BAT0"),
+                threshold: 20,
+                capacity: 20,
+                falling: true,
+            }]
+        );
+        assert_eq!(
+            state
+                .diff(&snapshot(false, &[("BAT0", "Discharging", 5)]))
+                .len(),
+            2
+        );
+        assert!(state
+            .diff(&snapshot(false, &[("BAT0", "Discharging", 21)]))
+            .is_empty());
+
+        let charging = old_state(vec![20], false, &[("BAT0", "Charging", 20)]);
+        assert_eq!(
+            charging.diff(&snapshot(false, &[("BAT0", "Charging", 21)])),
+            [BatteryEvent::ThresholdCrossed {
+                name: String::from("BAT0"),
+                threshold: 20,
+                capacity: 21,
+                falling: false,
+            }]
+        );
+    }
+}
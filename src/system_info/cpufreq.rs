@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{
+    error::Error,
+    sysfs::{
+        self, is_writable, parse_optional_attribute, read_optional_attribute, write_attribute,
+    },
+};
+
+pub const CPU_PATH: &str = "/sys/devices/system/cpu";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CpuFreq {
+    /// Index of the core, as in `cpuN`
+    pub cpu: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub governor: Option<String>,
+    pub available_governors: Vec<String>,
+    pub governor_writable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_performance_preference: Option<String>,
+    pub available_energy_performance_preferences: Vec<String>,
+    pub energy_performance_preference_writable: bool,
+    /// Frequencies in kHz
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_frequency: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_frequency: Option<u64>,
+}
+
+/// Reads a space-separated list attribute, which is empty if it can't be read
+fn read_list(path: &Path, buffer: &mut String) -> Vec<String> {
+    read_optional_attribute(path, buffer)
+        .map(|value| value.split_whitespace().map(str::to_owned).collect())
+        .unwrap_or_default()
+}
+
+/// Attributes that can't be read are skipped, e.g. `energy_performance_*` only exist with
+/// some drivers
+fn read_cpufreq(dir: &Path, cpu: u32, buffer: &mut String) -> CpuFreq {
+    let governor_path = dir.join("scaling_governor");
+    let preference_path = dir.join("energy_performance_preference");
+
+    CpuFreq {
+        cpu,
+        governor: read_optional_attribute(&governor_path, buffer),
+        available_governors: read_list(&dir.join("scaling_available_governors"), buffer),
+        governor_writable: is_writable(&governor_path),
+        energy_performance_preference: read_optional_attribute(&preference_path, buffer),
+        available_energy_performance_preferences: read_list(
+            &dir.join("energy_performance_available_preferences"),
+            buffer,
+        ),
+        energy_performance_preference_writable: is_writable(&preference_path),
+        frequency: parse_optional_attribute(&dir.join("scaling_cur_freq"), buffer),
+        min_frequency: parse_optional_attribute(&dir.join("scaling_min_freq"), buffer),
+        max_frequency: parse_optional_attribute(&dir.join("scaling_max_freq"), buffer),
+    }
+}
+
+pub fn get_cpufreq() -> Result<Vec<CpuFreq>, Error> {
+    get_cpufreq_in(Path::new(CPU_PATH))
+}
+
+/// Same as `get_cpufreq`, but reads cores from `root` instead of `/sys/devices/system/cpu`.
+/// Cores without cpufreq support are skipped
+pub fn get_cpufreq_in(root: &Path) -> Result<Vec<CpuFreq>, Error> {
+    let mut cpus = Vec::new();
+    let mut buffer = String::with_capacity(64);
+    for entry in sysfs::read_dir(root)?
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+    {
+        let cpu = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.strip_prefix("cpu"))
+            .and_then(|index| index.parse::<u32>().ok());
+
+        let Some(cpu) = cpu else {
+            continue;
+        };
+
+        let dir = entry.path().join("cpufreq");
+        if dir.is_dir() {
+            cpus.push(read_cpufreq(&dir, cpu, &mut buffer));
+        }
+    }
+
+    cpus.sort_by_key(|info| info.cpu);
+    Ok(cpus)
+}
+
+/// Writes `value` to `attribute` of the given core, or of every core if `cpu` is `None`
+fn write_cpufreq_attribute(
+    root: &Path,
+    cpu: Option<u32>,
+    attribute: &str,
+    value: &str,
+) -> Result<(), Error> {
+    let cpus = match cpu {
+        Some(cpu) => vec![cpu],
+        None => get_cpufreq_in(root)?
+            .into_iter()
+            .map(|info| info.cpu)
+            .collect(),
+    };
+
+    for cpu in cpus {
+        let dir = root.join(format!("cpu{cpu}/cpufreq"));
+        if !dir.is_dir() {
+            return Err(Error::NoDevice(format!("cpu{cpu}")));
+        }
+
+        write_attribute(&dir.join(attribute), value)?;
+    }
+
+    Ok(())
+}
+
+pub fn set_governor(cpu: Option<u32>, governor: &str) -> Result<(), Error> {
+    set_governor_in(Path::new(CPU_PATH), cpu, governor)
+}
+
+/// Same as `set_governor`, but writes to `root` instead of `/sys/devices/system/cpu`
+pub fn set_governor_in(root: &Path, cpu: Option<u32>, governor: &str) -> Result<(), Error> {
+    write_cpufreq_attribute(root, cpu, "scaling_governor", governor)
+}
+
+pub fn set_energy_performance_preference(cpu: Option<u32>, preference: &str) -> Result<(), Error> {
+    set_energy_performance_preference_in(Path::new(CPU_PATH), cpu, preference)
+}
+
+/// Same as `set_energy_performance_preference`, but writes to `root` instead of `/sys/devices/system/cpu`
+pub fn set_energy_performance_preference_in(
+    root: &Path,
+    cpu: Option<u32>,
+    preference: &str,
+) -> Result<(), Error> {
+    write_cpufreq_attribute(root, cpu, "energy_performance_preference", preference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_info::sysfs::fixture::Fixture;
+
+    #[test]
+    fn reads_cpufreq() {
+        let fixture = Fixture::new();
+        fixture
+            .write("cpu0/cpufreq/scaling_governor", "powersave\n")
+            .write(
+                "cpu0/cpufreq/scaling_available_governors",
+                "performance powersave\n",
+            )
+            .write(
+                "cpu0/cpufreq/energy_performance_preference",
+                "balance_power\n",
+            )
+            .write(
+                "cpu0/cpufreq/energy_performance_available_preferences",
+                "default performance balance_performance balance_power power\n",
+            )
+            .write("cpu0/cpufreq/scaling_cur_freq", "1800000\n")
+            .write("cpu0/cpufreq/scaling_min_freq", "400000\n")
+            .write("cpu0/cpufreq/scaling_max_freq", "4200000\n")
+            .write("cpu1/online", "1\n")
+            .write("cpuidle/current_driver", "intel_idle\n");
+
+        let cpus = get_cpufreq_in(fixture.path()).unwrap();
+        assert_eq!(cpus.len(), 1);
+
+        let cpu = &cpus[0];
+        assert_eq!(cpu.cpu, 0);
+        assert_eq!(cpu.governor.as_deref(), Some("powersave"));
+        assert_eq!(cpu.available_governors, ["performance", "powersave"]);
+        assert!(cpu.governor_writable);
+        assert_eq!(
+            cpu.energy_performance_preference.as_deref(),
+            Some("balance_power")
+        );
+        assert_eq!(cpu.available_energy_performance_preferences.len(), 5);
+        assert_eq!(cpu.frequency, Some(1_800_000));
+        assert_eq!(cpu.min_frequency, Some(400_000));
+        assert_eq!(cpu.max_frequency, Some(4_200_000));
+    }
+
+    #[test]
+    fn missing_attributes_dont_hide_cores() {
+        let fixture = Fixture::new();
+        fixture
+            .write("cpu0/cpufreq/scaling_governor", "schedutil\n")
+            .unreadable("cpu0/cpufreq/scaling_cur_freq")
+            .write("cpu1/cpufreq/scaling_governor", "schedutil\n")
+            .write("cpu1/cpufreq/scaling_max_freq", "garbage\n");
+
+        let cpus = get_cpufreq_in(fixture.path()).unwrap();
+        assert_eq!(cpus.len(), 2);
+        for cpu in &cpus {
+            assert_eq!(cpu.governor.as_deref(), Some("schedutil"));
+            assert!(cpu.available_governors.is_empty());
+            assert_eq!(cpu.energy_performance_preference, None);
+            assert!(!cpu.energy_performance_preference_writable);
+        }
+        assert_eq!(cpus[0].frequency, None);
+        assert_eq!(cpus[1].max_frequency, None);
+    }
+
+    #[test]
+    fn sets_governor() {
+        let fixture = Fixture::new();
+        fixture
+            .write("cpu0/cpufreq/scaling_governor", "powersave\n")
+            .write("cpu1/cpufreq/scaling_governor", "powersave\n");
+
+        set_governor_in(fixture.path(), Some(1), "performance").unwrap();
+        let cpus = get_cpufreq_in(fixture.path()).unwrap();
+        assert_eq!(cpus[0].governor.as_deref(), Some("powersave"));
+        assert_eq!(cpus[1].governor.as_deref(), Some("performance"));
+
+        set_governor_in(fixture.path(), None, "schedutil").unwrap();
+        let cpus = get_cpufreq_in(fixture.path()).unwrap();
+        assert!(cpus
+            .iter()
+            .all(|cpu| cpu.governor.as_deref() == Some("schedutil")));
+
+        assert!(matches!(
+            set_governor_in(fixture.path(), Some(2), "performance"),
+            Err(Error::NoDevice(_))
+        ));
+    }
+}
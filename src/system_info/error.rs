@@ -0,0 +1,12 @@
+use std::{io, path::PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("I/O error")]
+    Io(#[from] io::Error),
+    #[error("Failed to parse `{0}`")]
+    Parse(PathBuf),
+    #[error("Device `{0}` not found")]
+    NoDevice(String),
+}
@@ -1 +1,9 @@
+pub mod backlight;
 pub mod battery;
+pub mod battery_monitor;
+pub mod cpufreq;
+pub mod error;
+pub mod pressure;
+pub mod sampler;
+pub mod sensors;
+pub mod sysfs;
@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{error::Error, sysfs::read_attribute};
+
+pub const PRESSURE_PATH: &str = "/proc/pressure";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct PressureStats {
+    /// Percentage of time stalled over the last 10 seconds
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    /// Total stall time in µs
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Pressure {
+    /// Time at least some tasks were stalled
+    pub some: PressureStats,
+    /// Time all non-idle tasks were stalled at once.
+    /// Not reported for cpu by older kernels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub full: Option<PressureStats>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SystemPressure {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<Pressure>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io: Option<Pressure>,
+}
+
+/// Parses `avg10=0.00 avg60=0.00 avg300=0.00 total=0`
+fn parse_stats(fields: &str) -> Option<PressureStats> {
+    let mut stats = PressureStats::default();
+    for field in fields.split_whitespace() {
+        let (key, value) = field.split_once('=')?;
+        match key {
+            "avg10" => stats.avg10 = value.parse().ok()?,
+            "avg60" => stats.avg60 = value.parse().ok()?,
+            "avg300" => stats.avg300 = value.parse().ok()?,
+            "total" => stats.total = value.parse().ok()?,
+            _ => {}
+        }
+    }
+
+    Some(stats)
+}
+
+/// Reads a PSI file, returning `None` if the kernel doesn't support PSI
+pub fn read_pressure(path: &Path) -> Result<Option<Pressure>, Error> {
+    let mut buffer = String::with_capacity(128);
+    let Some(contents) = read_attribute(path, &mut buffer)? else {
+        return Ok(None);
+    };
+
+    let mut some = None;
+    let mut full = None;
+    for line in contents.lines() {
+        let Some((kind, fields)) = line.split_once(' ') else {
+            continue;
+        };
+
+        let stats = match kind {
+            "some" => &mut some,
+            "full" => &mut full,
+            _ => continue,
+        };
+        *stats = Some(parse_stats(fields).ok_or_else(|| Error::Parse(path.to_owned()))?);
+    }
+
+    match some {
+        Some(some) => Ok(Some(Pressure { some, full })),
+        None => Err(Error::Parse(path.to_owned())),
+    }
+}
+
+pub fn get_pressure() -> Result<SystemPressure, Error> {
+    get_pressure_in(Path::new(PRESSURE_PATH))
+}
+
+/// Same as `get_pressure`, but reads PSI files from `root` instead of `/proc/pressure`
+pub fn get_pressure_in(root: &Path) -> Result<SystemPressure, Error> {
+    Ok(SystemPressure {
+        cpu: read_pressure(&root.join("cpu"))?,
+        memory: read_pressure(&root.join("memory"))?,
+        io: read_pressure(&root.join("io"))?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_info::sysfs::fixture::Fixture;
+
+    #[test]
+    fn parses_stats() {
+        let stats = parse_stats("avg10=1.50 avg60=0.25 avg300=0.00 total=123456").unwrap();
+        assert_eq!(stats.avg10, 1.5);
+        assert_eq!(stats.avg60, 0.25);
+        assert_eq!(stats.avg300, 0.0);
+        assert_eq!(stats.total, 123456);
+
+        assert!(parse_stats("avg10=abc avg60=0.00 avg300=0.00 total=0").is_none());
+        assert!(parse_stats("avg10").is_none());
+    }
+
+    #[test]
+    fn reads_pressure() {
+        let fixture = Fixture::new();
+        fixture
+            .write("cpu", "some avg10=2.00 avg60=1.00 avg300=0.50 total=1000\n")
+            .write(
+                "memory",
+                "some avg10=0.00 avg60=0.00 avg300=0.00 total=10\n\
+                 full avg10=0.00 avg60=0.00 avg300=0.00 total=5\n",
+            )
+            .write("io", "full avg10=0.00 avg60=0.00 avg300=0.00 total=5\n");
+
+        let cpu = read_pressure(&fixture.path().join("cpu")).unwrap().unwrap();
+        assert_eq!(cpu.some.avg10, 2.0);
+        assert!(cpu.full.is_none());
+
+        let memory = read_pressure(&fixture.path().join("memory"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(memory.some.total, 10);
+        assert_eq!(memory.full.unwrap().total, 5);
+
+        // `some` is always reported
+        assert!(matches!(
+            get_pressure_in(fixture.path()),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn missing_pressure() {
+        let fixture = Fixture::new();
+        let pressure = get_pressure_in(fixture.path()).unwrap();
+        assert!(pressure.cpu.is_none());
+        assert!(pressure.memory.is_none());
+        assert!(pressure.io.is_none());
+    }
+}
@@ -0,0 +1,221 @@
+use async_channel::Sender;
+use gtk::glib::{self, JoinHandle};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+use sysinfo::{CpuExt, CpuRefreshKind, NetworkExt, RefreshKind, System, SystemExt};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct NetworkRate {
+    /// Bytes received per second
+    pub received: f64,
+    /// Bytes transmitted per second
+    pub transmitted: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sample {
+    /// Seconds since the epoch
+    pub timestamp: f64,
+    /// Usage of all cpus in percent
+    pub cpu_usage: f32,
+    pub cpu_usages: Vec<f32>,
+    pub used_memory: u64,
+    pub total_memory: u64,
+    pub memory_percent: f64,
+    pub used_swap: u64,
+    pub total_swap: u64,
+    pub swap_percent: f64,
+    pub networks: HashMap<String, NetworkRate>,
+    /// Sum of all network rates
+    pub network: NetworkRate,
+}
+
+fn percent(used: u64, total: u64) -> f64 {
+    if total != 0 {
+        used as f64 * 100.0 / total as f64
+    } else {
+        0.0
+    }
+}
+
+fn take_sample(system: &mut System, elapsed: Duration) -> Sample {
+    system.refresh_cpu_specifics(CpuRefreshKind::new().with_cpu_usage());
+    system.refresh_memory();
+    system.refresh_networks();
+
+    let elapsed = elapsed.as_secs_f64().max(f64::EPSILON);
+    let mut network = NetworkRate {
+        received: 0.0,
+        transmitted: 0.0,
+    };
+    let networks = system
+        .networks()
+        .into_iter()
+        .map(|(iface, data)| {
+            let rate = NetworkRate {
+                received: data.received() as f64 / elapsed,
+                transmitted: data.transmitted() as f64 / elapsed,
+            };
+            network.received += rate.received;
+            network.transmitted += rate.transmitted;
+
+            (iface.clone(), rate)
+        })
+        .collect();
+
+    Sample {
+        timestamp: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64(),
+        cpu_usage: system.global_cpu_info().cpu_usage(),
+        cpu_usages: system.cpus().iter().map(|cpu| cpu.cpu_usage()).collect(),
+        used_memory: system.used_memory(),
+        total_memory: system.total_memory(),
+        memory_percent: percent(system.used_memory(), system.total_memory()),
+        used_swap: system.used_swap(),
+        total_swap: system.total_swap(),
+        swap_percent: percent(system.used_swap(), system.total_swap()),
+        networks,
+        network,
+    }
+}
+
+fn run(interval: Duration, stopped: Arc<AtomicBool>, sender: Sender<Sample>) {
+    let mut system = System::new_with_specifics(
+        RefreshKind::new()
+            .with_cpu(CpuRefreshKind::new().with_cpu_usage())
+            .with_memory()
+            .with_networks()
+            .with_networks_list(),
+    );
+
+    let mut last_refresh = Instant::now();
+    while !stopped.load(Ordering::Relaxed) {
+        std::thread::sleep(interval);
+
+        let now = Instant::now();
+        let sample = take_sample(&mut system, now - last_refresh);
+        last_refresh = now;
+
+        if sender.send_blocking(sample).is_err() {
+            break;
+        }
+    }
+}
+
+type Handler = Box<dyn FnMut(&Sample)>;
+
+struct State {
+    history: VecDeque<Sample>,
+    history_size: usize,
+    handlers: Vec<Handler>,
+}
+
+/// Refreshes a `System` on a separate thread, delivering samples on the default main context
+pub struct Sampler {
+    state: Rc<RefCell<State>>,
+    interval: Duration,
+    running: Option<(Arc<AtomicBool>, JoinHandle<()>)>,
+}
+
+impl Sampler {
+    /// Returns a new stopped sampler keeping the last `history_size` samples.
+    /// With a `history_size` of 0 no history is kept, and samples are only passed to handlers
+    pub fn new(interval: Duration, history_size: usize) -> Self {
+        Self {
+            state: Rc::new(RefCell::new(State {
+                history: VecDeque::with_capacity(history_size),
+                history_size,
+                handlers: Vec::new(),
+            })),
+            interval,
+            running: None,
+        }
+    }
+
+    pub fn connect<F: FnMut(&Sample) + 'static>(&self, f: F) {
+        self.state.borrow_mut().handlers.push(Box::new(f));
+    }
+
+    pub fn running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    pub fn start(&mut self) {
+        if self.running() {
+            return;
+        }
+
+        let (sender, receiver) = async_channel::bounded(4);
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let interval = self.interval;
+        let stopped_ref = stopped.clone();
+        std::thread::spawn(move || run(interval, stopped_ref, sender));
+
+        let state = self.state.clone();
+        let handle = glib::MainContext::default().spawn_local(async move {
+            while let Ok(sample) = receiver.recv().await {
+                {
+                    let mut state = state.borrow_mut();
+                    if state.history_size > 0 {
+                        while state.history.len() >= state.history_size {
+                            state.history.pop_front();
+                        }
+
+                        state.history.push_back(sample.clone());
+                    }
+                }
+
+                // Take handlers out to allow connecting new ones from inside of them
+                let mut handlers = std::mem::take(&mut state.borrow_mut().handlers);
+                for handler in handlers.iter_mut() {
+                    handler(&sample);
+                }
+
+                let mut state = state.borrow_mut();
+                handlers.append(&mut state.handlers);
+                state.handlers = handlers;
+            }
+        });
+
+        self.running = Some((stopped, handle));
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((stopped, handle)) = self.running.take() {
+            stopped.store(true, Ordering::Relaxed);
+            // Dropping the receiver stops the thread if it's blocked on sending
+            handle.abort();
+        }
+    }
+
+    /// Returns collected samples, from oldest to newest
+    pub fn history(&self) -> Vec<Sample> {
+        self.state.borrow().history.iter().cloned().collect()
+    }
+
+    pub fn latest(&self) -> Option<Sample> {
+        self.state.borrow().history.back().cloned()
+    }
+
+    pub fn clear_history(&self) {
+        self.state.borrow_mut().history.clear();
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
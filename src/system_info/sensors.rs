@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use super::{
+    error::Error,
+    sysfs::{self, parse_optional_attribute, read_optional_attribute},
+};
+
+pub const SYSFS_CLASS_PATH: &str = "/sys/class";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Temperature {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Temperature in °C
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Fan {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Speed in RPM
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HwmonDevice {
+    /// Directory name, e.g. `hwmon0`
+    pub id: String,
+    /// Driver name, e.g. `coretemp`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub temperatures: Vec<Temperature>,
+    pub fans: Vec<Fan>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThermalZone {
+    /// Directory name, e.g. `thermal_zone0`
+    pub id: String,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub typ: Option<String>,
+    /// Temperature in °C
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Sensors {
+    pub hwmon: Vec<HwmonDevice>,
+    pub thermal: Vec<ThermalZone>,
+}
+
+/// Converts millidegrees to degrees
+fn parse_temperature(path: &Path, buffer: &mut String) -> Option<f64> {
+    parse_optional_attribute::<f64>(path, buffer).map(|value| value / 1000.0)
+}
+
+/// Returns sorted indices of `{prefix}N{suffix}` files in `dir`
+fn find_indices(dir: &Path, prefix: &str, suffix: &str) -> Result<Vec<u32>, Error> {
+    let mut indices = Vec::new();
+    for entry in sysfs::read_dir(dir)?
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+    {
+        let file_name = entry.file_name();
+        let index = file_name
+            .to_str()
+            .and_then(|name| name.strip_prefix(prefix))
+            .and_then(|name| name.strip_suffix(suffix))
+            .and_then(|index| index.parse::<u32>().ok());
+
+        if let Some(index) = index {
+            indices.push(index);
+        }
+    }
+
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(indices)
+}
+
+/// Returns sorted subdirectories of `dir` starting with `prefix`
+fn find_devices(dir: &Path, prefix: &str) -> Result<Vec<String>, Error> {
+    let mut devices = Vec::new();
+    for entry in sysfs::read_dir(dir)?
+        .into_iter()
+        .flatten()
+        .filter_map(Result::ok)
+    {
+        if let Ok(name) = entry.file_name().into_string() {
+            if name.starts_with(prefix) {
+                devices.push(name);
+            }
+        }
+    }
+
+    // Sort numerically, so that `hwmon10` goes after `hwmon9`
+    devices.sort_by_key(|name| name[prefix.len()..].parse::<u32>().unwrap_or(u32::MAX));
+    Ok(devices)
+}
+
+fn read_hwmon_device(dir: &Path, id: String, buffer: &mut String) -> Result<HwmonDevice, Error> {
+    // Older drivers put attributes into the `device` subdirectory
+    let dir = if dir.join("name").exists() || !dir.join("device/name").exists() {
+        dir.to_owned()
+    } else {
+        dir.join("device")
+    };
+
+    let mut temperatures = Vec::new();
+    for index in find_indices(&dir, "temp", "_input")? {
+        temperatures.push(Temperature {
+            label: read_optional_attribute(&dir.join(format!("temp{index}_label")), buffer),
+            input: parse_temperature(&dir.join(format!("temp{index}_input")), buffer),
+            max: parse_temperature(&dir.join(format!("temp{index}_max")), buffer),
+            critical: parse_temperature(&dir.join(format!("temp{index}_crit")), buffer),
+        });
+    }
+
+    let mut fans = Vec::new();
+    for index in find_indices(&dir, "fan", "_input")? {
+        fans.push(Fan {
+            label: read_optional_attribute(&dir.join(format!("fan{index}_label")), buffer),
+            input: parse_optional_attribute(&dir.join(format!("fan{index}_input")), buffer),
+            min: parse_optional_attribute(&dir.join(format!("fan{index}_min")), buffer),
+            max: parse_optional_attribute(&dir.join(format!("fan{index}_max")), buffer),
+        });
+    }
+
+    Ok(HwmonDevice {
+        id,
+        name: read_optional_attribute(&dir.join("name"), buffer),
+        temperatures,
+        fans,
+    })
+}
+
+fn read_thermal_zone(dir: &Path, id: String, buffer: &mut String) -> Result<ThermalZone, Error> {
+    let mut critical = None;
+    for index in find_indices(dir, "trip_point_", "_type")? {
+        let trip_type =
+            read_optional_attribute(&dir.join(format!("trip_point_{index}_type")), buffer);
+        if trip_type.as_deref() == Some("critical") {
+            critical = parse_temperature(&dir.join(format!("trip_point_{index}_temp")), buffer);
+            break;
+        }
+    }
+
+    Ok(ThermalZone {
+        id,
+        typ: read_optional_attribute(&dir.join("type"), buffer),
+        temperature: parse_temperature(&dir.join("temp"), buffer),
+        critical,
+    })
+}
+
+/// Devices that can't be listed are skipped, so that one broken sensor doesn't hide the others
+pub fn get_hwmon_devices_in(root: &Path) -> Result<Vec<HwmonDevice>, Error> {
+    let hwmon_dir = root.join("hwmon");
+    let mut buffer = String::with_capacity(16);
+    Ok(find_devices(&hwmon_dir, "hwmon")?
+        .into_iter()
+        .filter_map(|id| read_hwmon_device(&hwmon_dir.join(&id), id, &mut buffer).ok())
+        .collect())
+}
+
+/// Zones that can't be listed are skipped, like in `get_hwmon_devices_in`
+pub fn get_thermal_zones_in(root: &Path) -> Result<Vec<ThermalZone>, Error> {
+    let thermal_dir = root.join("thermal");
+    let mut buffer = String::with_capacity(16);
+    Ok(find_devices(&thermal_dir, "thermal_zone")?
+        .into_iter()
+        .filter_map(|id| read_thermal_zone(&thermal_dir.join(&id), id, &mut buffer).ok())
+        .collect())
+}
+
+pub fn get_sensors() -> Result<Sensors, Error> {
+    get_sensors_in(Path::new(SYSFS_CLASS_PATH))
+}
+
+/// Same as `get_sensors`, but reads devices from `root` instead of `/sys/class`
+pub fn get_sensors_in(root: &Path) -> Result<Sensors, Error> {
+    Ok(Sensors {
+        hwmon: get_hwmon_devices_in(root)?,
+        thermal: get_thermal_zones_in(root)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::system_info::sysfs::fixture::Fixture;
+
+    #[test]
+    fn reads_sensors() {
+        let fixture = Fixture::new();
+        fixture
+            .write("hwmon/hwmon0/name", "coretemp\n")
+            .write("hwmon/hwmon0/temp1_label", "Package id 0\n")
+            .write("hwmon/hwmon0/temp1_input", "45000\n")
+            .write("hwmon/hwmon0/temp1_crit", "100000\n")
+            .write("hwmon/hwmon0/fan1_input", "1200\n")
+            .write("thermal/thermal_zone0/type", "x86_pkg_temp\n")
+            .write("thermal/thermal_zone0/temp", "46000\n")
+            .write("thermal/thermal_zone0/trip_point_0_type", "passive\n")
+            .write("thermal/thermal_zone0/trip_point_0_temp", "90000\n")
+            .write("thermal/thermal_zone0/trip_point_1_type", "critical\n")
+            .write("thermal/thermal_zone0/trip_point_1_temp", "105000\n");
+
+        let sensors = get_sensors_in(fixture.path()).unwrap();
+        let hwmon = &sensors.hwmon[0];
+        assert_eq!(hwmon.name.as_deref(), Some("coretemp"));
+        assert_eq!(hwmon.temperatures[0].label.as_deref(), Some("Package id 0"));
+        assert_eq!(hwmon.temperatures[0].input, Some(45.0));
+        assert_eq!(hwmon.temperatures[0].critical, Some(100.0));
+        assert_eq!(hwmon.fans[0].input, Some(1200));
+
+        let zone = &sensors.thermal[0];
+        assert_eq!(zone.typ.as_deref(), Some("x86_pkg_temp"));
+        assert_eq!(zone.temperature, Some(46.0));
+        assert_eq!(zone.critical, Some(105.0));
+    }
+
+    #[test]
+    fn broken_sensors_dont_hide_others() {
+        let fixture = Fixture::new();
+        fixture
+            .write("hwmon/hwmon0/name", "amdgpu\n")
+            .unreadable("hwmon/hwmon0/temp1_input")
+            .write("hwmon/hwmon0/fan1_input", "garbage\n")
+            .write("hwmon/hwmon1/name", "coretemp\n")
+            .write("hwmon/hwmon1/temp1_input", "45000\n")
+            .write("thermal/thermal_zone0/type", "iwlwifi_1\n")
+            .unreadable("thermal/thermal_zone0/temp")
+            .write("thermal/thermal_zone1/type", "x86_pkg_temp\n")
+            .write("thermal/thermal_zone1/temp", "46000\n");
+
+        let sensors = get_sensors_in(fixture.path()).unwrap();
+        assert_eq!(sensors.hwmon.len(), 2);
+        assert_eq!(sensors.hwmon[0].temperatures[0].input, None);
+        assert_eq!(sensors.hwmon[0].fans[0].input, None);
+        assert_eq!(sensors.hwmon[1].temperatures[0].input, Some(45.0));
+
+        assert_eq!(sensors.thermal.len(), 2);
+        assert_eq!(sensors.thermal[0].temperature, None);
+        assert_eq!(sensors.thermal[1].temperature, Some(46.0));
+    }
+
+    #[test]
+    fn no_sensors() {
+        let fixture = Fixture::new();
+        let sensors = get_sensors_in(fixture.path()).unwrap();
+        assert!(sensors.hwmon.is_empty());
+        assert!(sensors.thermal.is_empty());
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    ffi::CString,
+    fs::{self, File, ReadDir},
+    io::{self, Read},
+    os::unix::ffi::OsStrExt,
+    path::Path,
+    str::FromStr,
+};
+
+use super::error::Error;
+
+pub(super) fn read_to_string_buf<P: AsRef<Path>>(
+    path: P,
+    buffer: &mut String,
+) -> io::Result<&mut String> {
+    let mut file = File::open(path)?;
+    buffer.clear();
+    file.read_to_string(buffer)?;
+    Ok(buffer)
+}
+
+/// Some drivers return `ENODEV` for attributes they don't support,
+/// and PSI files return `EOPNOTSUPP` when PSI is disabled at boot
+pub(super) fn is_missing(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::NotFound
+        || matches!(err.raw_os_error(), Some(libc::ENODEV | libc::EOPNOTSUPP))
+}
+
+/// Reads a sysfs attribute, returning `None` if the device doesn't provide it
+pub(super) fn read_attribute<'a>(
+    path: &Path,
+    buffer: &'a mut String,
+) -> Result<Option<&'a str>, Error> {
+    match read_to_string_buf(path, buffer) {
+        Ok(value) => Ok(Some(value.trim_end_matches('\n'))),
+        Err(err) if is_missing(&err) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub(super) fn read_string_attribute(
+    path: &Path,
+    buffer: &mut String,
+) -> Result<Option<String>, Error> {
+    Ok(read_attribute(path, buffer)?.map(str::to_owned))
+}
+
+pub(super) fn parse_attribute<T: FromStr>(
+    path: &Path,
+    buffer: &mut String,
+) -> Result<Option<T>, Error> {
+    match read_attribute(path, buffer)? {
+        Some(value) => value
+            .trim()
+            .parse::<T>()
+            .map(Some)
+            .map_err(|_| Error::Parse(path.to_owned())),
+        None => Ok(None),
+    }
+}
+
+/// Like `read_string_attribute`, but treats read errors as a missing attribute.
+/// Drivers return all kinds of errors, e.g. `ENODATA` or `EIO` from a powered down device,
+/// which shouldn't hide the rest of its attributes
+pub(super) fn read_optional_attribute(path: &Path, buffer: &mut String) -> Option<String> {
+    read_string_attribute(path, buffer).ok().flatten()
+}
+
+/// Like `parse_attribute`, but treats read and parse errors as a missing attribute
+pub(super) fn parse_optional_attribute<T: FromStr>(path: &Path, buffer: &mut String) -> Option<T> {
+    parse_attribute(path, buffer).ok().flatten()
+}
+
+pub(super) fn write_attribute(path: &Path, value: &str) -> Result<(), Error> {
+    fs::write(path, value)?;
+    Ok(())
+}
+
+/// Checks whether the current user is allowed to write to an attribute
+pub(super) fn is_writable(path: &Path) -> bool {
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+
+    unsafe { libc::access(path.as_ptr(), libc::W_OK) == 0 }
+}
+
+/// Reads a directory, returning `None` if it doesn't exist
+pub(super) fn read_dir(path: &Path) -> Result<Option<ReadDir>, Error> {
+    match path.read_dir() {
+        Ok(entries) => Ok(Some(entries)),
+        Err(err) if is_missing(&err) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Fake sysfs trees for the `*_in(root)` functions
+#[cfg(test)]
+pub(super) mod fixture {
+    use std::{
+        fs,
+        path::{Path, PathBuf},
+        process,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    /// Temporary directory that is removed on drop
+    pub struct Fixture(PathBuf);
+
+    impl Fixture {
+        pub fn new() -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "crabshell-sysfs-{}-{}",
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        pub fn path(&self) -> &Path {
+            &self.0
+        }
+
+        /// Writes an attribute, creating its parent directories
+        pub fn write(&self, attribute: &str, value: &str) -> &Self {
+            let path = self.0.join(attribute);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, value).unwrap();
+            self
+        }
+
+        /// Creates a directory in place of an attribute, so that reading it fails with `EISDIR`
+        pub fn unreadable(&self, attribute: &str) -> &Self {
+            fs::create_dir_all(self.0.join(attribute)).unwrap();
+            self
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+}